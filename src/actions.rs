@@ -1,4 +1,4 @@
-use std::slice::Iter;
+use std::{slice::Iter, path::PathBuf};
 use crate::ui::AlertDialogStyle;
 
 
@@ -8,17 +8,29 @@ use crate::ui::AlertDialogStyle;
 pub enum Actions {
     DeleteLog(i64),
     DeleteRace(i64),
+    DeleteStation(i64),
 
     ShowError(String),
+    ShowInfo(String),
+    ShowStatus(String),
     ShowConfirm(String, AlertDialogStyle, Box<Actions>),
 
     CreateLogWanted,
     FocusLog(Option<i64>),
     EditLog(i64),
+    // Saves log being created although it is a duplicate, it is flagged as one and scores no points.
+    SaveDupeLog,
+    SetLogFilter(String),
 
     EditRace(i64),
 
-    ToggleMap
+    OpenDatabase(PathBuf),
+    OpenDatabaseReadOnly(PathBuf),
+    RestoreBackup(PathBuf),
+
+    ToggleMap,
+    ToggleDetails,
+    ToggleStats
 }
 
 
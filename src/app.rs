@@ -1,18 +1,21 @@
 use std::{time::{Instant, Duration}, io::Stdout};
 
-use crossterm::{event::{Event, self, KeyCode}, Result};
-use ratatui::{Terminal, backend::CrosstermBackend, prelude::Rect };
+use crossterm::{event::{Event, self, KeyCode, KeyModifiers}, Result};
+use ratatui::{Terminal, backend::CrosstermBackend, prelude::Rect, widgets::Paragraph, style::{Style, Color} };
 
-use crate::{actions::Actions, app_context::AppContext, common_types::RenderFrame, traits::{EventResult, RenderResult, UIEvents}, ui::{self, define_typed_element, AlertDialog, AlertDialogButton, AlertDialogStyle, CreateLogDialog, ManageRacesDialog}, ui_handler::UIHandler};
+use crate::{actions::Actions, app_context::AppContext, app_errors::AppError, common_types::RenderFrame, data::dxcc, traits::{EventResult, RenderResult, UIEvents}, ui::{self, define_typed_element, AlertDialog, AlertDialogButton, AlertDialogStyle, BackupsDialog, CreateLogDialog, ExportDialog, FilterDialog, ImportDialog, LogbooksDialog, ManageRacesDialog, ManageStationsDialog, SettingsDialog}, ui_handler::UIHandler};
 use crate::traits::UIElement;
 
 
 const TICK_RATE :Duration = std::time::Duration::from_millis(60);
+const STATUS_DURATION :Duration = std::time::Duration::from_secs(5);
 pub struct App {
     ui_elements :UIHandler,
     dialogs :UIHandler,
 
     alert_dialog :Option<AlertDialog>,
+    // Short message shown at the bottom of the screen with time it was set.
+    status :Option<(String, Instant)>,
 }
 define_typed_element!(App);
 
@@ -25,11 +28,24 @@ impl App {
         let mut dialogs = UIHandler::default();
         dialogs.add(Box::new(CreateLogDialog::default()));
         dialogs.add(Box::new(ManageRacesDialog::default()));
+        dialogs.add(Box::new(ManageStationsDialog::default()));
+        dialogs.add(Box::new(ExportDialog::default()));
+        dialogs.add(Box::new(ImportDialog::default()));
+        dialogs.add(Box::new(SettingsDialog::default()));
+        dialogs.add(Box::new(LogbooksDialog::default()));
+        dialogs.add(Box::new(BackupsDialog::default()));
+        dialogs.add(Box::new(FilterDialog::default()));
         dialogs.add(Box::new(ui::WorldMap::default()));
+        dialogs.add(Box::new(ui::DetailsWindow::default()));
+        dialogs.add(Box::new(ui::StatsDialog::default()));
+        // Opened by plain key, has to be last so dialogs with text inputs get the key first.
+        // Once opened it captures input, so keys of details window are typed into search.
+        dialogs.add(Box::new(ui::SearchDialog::default()));
 
 
         App {
             alert_dialog: None,
+            status: None,
             ui_elements: handler,
             dialogs,
         }
@@ -41,6 +57,10 @@ impl App {
         let mut last_tick = Instant::now();
         let mut frame_index :u8 = 0;
 
+        if let Err(error) = dxcc::init() {
+            self.pop_error(format!("{}, using bundled one", error));
+        }
+
         terminal.clear()?;
 
         loop {
@@ -100,6 +120,9 @@ impl App {
         ///// Render common dialogs on top:
         self.dialogs.draw_all(frame_index, f, app_ctx)?;
 
+        ///// Status message over bottom border:
+        self.draw_status(f);
+
         ///// Process accumulated actions:
         self.process_actions(app_ctx);
         Ok(())
@@ -122,6 +145,38 @@ impl App {
         }
     }
 
+    fn draw_status(&mut self, f :&mut RenderFrame) {
+        let Some((text, since)) = self.status.as_ref() else {
+            return;
+        };
+        if since.elapsed() > STATUS_DURATION {
+            self.status = None;
+            return;
+        }
+
+        let size = f.size();
+        if size.height < 1 || size.width < 4 {
+            return;
+        }
+        let width = (text.chars().count() as u16 + 2).min(size.width - 4);
+        f.render_widget(
+            Paragraph::new(format!(" {} ", text)).style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+            Rect::new(size.x + 2, size.y + size.height - 1, width, 1)
+        );
+    }
+
+    fn undo(&mut self, redo :bool, app_ctx :&mut AppContext) {
+        let (result, verb) = match redo {
+            false => (app_ctx.data.undo(), "Undone"),
+            true => (app_ctx.data.redo(), "Redone"),
+        };
+        match result {
+            Ok(Some(description)) => app_ctx.actions.add(Actions::ShowStatus(format!("{}: {}", verb, description))),
+            Ok(None) => app_ctx.actions.add(Actions::ShowStatus(format!("Nothing to {}", if redo { "redo" } else { "undo" }))),
+            Err(err) => app_ctx.actions.add(Actions::ShowError(format!("{} failed: {}", if redo { "Redo" } else { "Undo" }, err))),
+        }
+    }
+
     fn on_tick(&mut self) {
 
     }
@@ -135,6 +190,15 @@ impl App {
         );
     }
 
+    fn pop_info(&mut self, text :String) {
+        self.alert_dialog = Some(AlertDialog::new(
+            text,
+            AlertDialogButton::OK,
+            AlertDialogStyle::Default,
+            None)
+        );
+    }
+
     fn pop_confirm(&mut self, text :String, style :AlertDialogStyle, action_after :Option<Actions>) {
         self.alert_dialog = Some(AlertDialog::new(
             text,
@@ -160,6 +224,14 @@ impl UIElement for App {
                 self.ui_elements.focus_next();
                 EventResult::Handled
             },
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.undo(false, app_ctx);
+                EventResult::Handled
+            },
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.undo(true, app_ctx);
+                EventResult::Handled
+            },
             KeyCode::Char('m') => {
                 app_ctx.actions.add(Actions::ToggleMap);
                 EventResult::Handled
@@ -168,13 +240,48 @@ impl UIElement for App {
         }
     }
 
-    fn on_action(&mut self, action :&Actions, _app_ctx :&mut AppContext) -> EventResult {
+    fn on_action(&mut self, action :&Actions, app_ctx :&mut AppContext) -> EventResult {
         match action {
+            Actions::RestoreBackup(path) => {
+                match app_ctx.restore_backup(path) {
+                    Ok(_) => {
+                        app_ctx.actions.add(Actions::FocusLog(None));
+                        self.pop_info(format!("Logbook restored from {}", path.display()));
+                    },
+                    Err(err) => self.pop_error(format!("Unable to restore backup {}: {}", path.display(), err)),
+                }
+                EventResult::Handled
+            },
+
+            Actions::OpenDatabase(path) | Actions::OpenDatabaseReadOnly(path) => {
+                let read_only = matches!(action, Actions::OpenDatabaseReadOnly(_));
+                match app_ctx.open_database(path, read_only) {
+                    Ok(_) => app_ctx.actions.add(Actions::FocusLog(None)),
+                    Err(err @ AppError::SchemaTooNew { .. }) => self.pop_confirm(
+                        format!("{}. Open it read-only?", err),
+                        AlertDialogStyle::Warning,
+                        Some(Actions::OpenDatabaseReadOnly(path.clone()))
+                    ),
+                    Err(err) => self.pop_error(format!("Unable to open logbook {}: {}", path.display(), err)),
+                }
+                EventResult::Handled
+            },
+
             Actions::ShowError(text) => {
                 self.pop_error(text.clone());
                 EventResult::Handled
             },
 
+            Actions::ShowInfo(text) => {
+                self.pop_info(text.clone());
+                EventResult::Handled
+            },
+
+            Actions::ShowStatus(text) => {
+                self.status = Some((text.clone(), Instant::now()));
+                EventResult::Handled
+            },
+
             Actions::ShowConfirm(msg, style, on_confirm) => {
                 self.pop_confirm(msg.clone(), style.clone(), Some((**on_confirm).clone()));
                 EventResult::Handled
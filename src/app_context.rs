@@ -1,6 +1,6 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, path::Path};
 
-use crate::{data::Data, actions::ActionProcessor, app_errors::AppError, database::Database};
+use crate::{data::Data, actions::ActionProcessor, app_errors::AppError, database::{Database, RecentLogbooks}};
 
 pub struct AppContext<'a> {
     pub db :&'a RefCell<Database>,
@@ -16,4 +16,33 @@ impl<'a> AppContext<'a> {
             actions: ActionProcessor::default(),
         })
     }
+
+    /// Switches to logbook in given file. All data stores are re-created from the new database.
+    pub fn open_database(&mut self, path :&Path, read_only :bool) -> Result<(), AppError> {
+        let database = match read_only {
+            true => Database::open_read_only(path)?,
+            false => Database::from_path(path)?,
+        };
+        let previous = std::mem::replace(&mut *self.db.borrow_mut(), database);
+
+        match Data::new(self.db) {
+            Ok(data) => self.data = data,
+            Err(err) => {
+                // Keep working with previous logbook when new one can not be loaded.
+                *self.db.borrow_mut() = previous;
+                return Err(err);
+            }
+        }
+
+        // Failing to store recent list must not prevent opening the logbook.
+        let _ = RecentLogbooks::remember(path);
+        Ok(())
+    }
+
+    /// Restores logbook from backup file and reloads all data stores.
+    pub fn restore_backup(&mut self, backup_path :&Path) -> Result<(), AppError> {
+        self.db.borrow_mut().restore_backup(backup_path)?;
+        self.data = Data::new(self.db)?;
+        Ok(())
+    }
 }
\ No newline at end of file
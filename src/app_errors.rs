@@ -1,11 +1,47 @@
+use crate::data::position::LocatorError;
+
+
 #[derive(thiserror::Error, Debug)]
 pub enum AppError {
-    #[error("Failed to layout UI element")]
+    #[error("File system error: {0}")]
     FileSystemError(#[from] std::io::Error),
 
-    #[error("Failed to initialize database")]
+    #[error("Database error: {0}")]
     DatabaseError(#[from] rusqlite::Error),
 
-    #[error("Invalid QTH locator provided")]
-    InvalidQTHLocator
+    #[error("Invalid QTH locator: {0}")]
+    InvalidQTHLocator(#[from] LocatorError),
+
+    #[error("Own QTH locator is not configured, set it in settings")]
+    MissingOwnLocator,
+
+    #[error("Table {table} of the logbook has schema version {found}, this version of application supports up to {supported}")]
+    SchemaTooNew { table: String, found: u32, supported: usize },
+
+    #[error("Record {id} of {table} does not exist")]
+    MissingRecord { table: String, id: i64 },
+
+    #[error("Logbook is opened read-only, changes are not allowed")]
+    ReadOnly,
+
+    #[error("Failed to import file: {0}")]
+    ImportError(String),
+
+    #[error("Failed to export logs: {0}")]
+    ExportError(String),
+
+    #[error("Invalid filter {0}")]
+    InvalidFilter(String),
+
+    #[error("Invalid scoring rule {0}")]
+    InvalidScoring(String),
+
+    #[error("Invalid duplicate rule {0}")]
+    InvalidDupeRule(String),
+
+    #[error("Invalid coordinates: {0}")]
+    InvalidCoordinates(String),
+
+    #[error("Invalid country file: {0}")]
+    InvalidCountryFile(String)
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+// Band edges as defined by ADIF specification (https://adif.org/314/ADIF_314.htm#Band_Enumeration)
+
+pub struct Band {
+    pub name :&'static str,
+    pub lower_hz :i64,
+    pub upper_hz :i64,
+}
+
+const fn band(name :&'static str, lower_khz :i64, upper_khz :i64) -> Band {
+    Band {
+        name,
+        lower_hz: lower_khz * 1000,
+        upper_hz: upper_khz * 1000,
+    }
+}
+
+pub static BANDS :[Band; 30] = [
+    band("2190m", 135, 138),
+    band("630m", 472, 479),
+    band("160m", 1_800, 2_000),
+    band("80m", 3_500, 4_000),
+    band("60m", 5_060, 5_450),
+    band("40m", 7_000, 7_300),
+    band("30m", 10_100, 10_150),
+    band("20m", 14_000, 14_350),
+    band("17m", 18_068, 18_168),
+    band("15m", 21_000, 21_450),
+    band("12m", 24_890, 24_990),
+    band("10m", 28_000, 29_700),
+    band("8m", 40_000, 45_000),
+    band("6m", 50_000, 54_000),
+    band("4m", 70_000, 71_000),
+    band("2m", 144_000, 148_000),
+    band("1.25m", 222_000, 225_000),
+    band("70cm", 420_000, 450_000),
+    band("33cm", 902_000, 928_000),
+    band("23cm", 1_240_000, 1_300_000),
+    band("13cm", 2_300_000, 2_450_000),
+    band("9cm", 3_300_000, 3_500_000),
+    band("6cm", 5_650_000, 5_925_000),
+    band("3cm", 10_000_000, 10_500_000),
+    band("1.25cm", 24_000_000, 24_250_000),
+    band("6mm", 47_000_000, 47_200_000),
+    band("4mm", 75_500_000, 81_000_000),
+    band("2.5mm", 119_980_000, 123_000_000),
+    band("2mm", 134_000_000, 149_000_000),
+    band("1mm", 241_000_000, 250_000_000),
+];
+
+pub fn band_for_frequency(frequency_hz :i64) -> Option<&'static Band> {
+    BANDS.iter().find(|band| band.lower_hz <= frequency_hz && frequency_hz <= band.upper_hz)
+}
+
+pub fn band_by_name(name :&str) -> Option<&'static Band> {
+    BANDS.iter().find(|band| band.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Parses frequency as typed by operator. Decimal numbers and whole numbers below 1000 are MHz (144.300, 432).
+/// Larger whole numbers are kHz when they fall into a band (14074), otherwise MHz when those do
+/// (1296, 10368 typed on microwaves), and kHz again when they fall into none.
+pub fn parse_frequency(text :&str) -> Option<i64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let value :f64 = text.parse().ok()?;
+    let khz = (value * 1e3).round() as i64;
+    let mhz = (value * 1e6).round() as i64;
+    let hz = match text.contains('.') || value < 1000.0 {
+        true => mhz,
+        false if band_for_frequency(khz).is_none() && band_for_frequency(mhz).is_some() => mhz,
+        false => khz,
+    };
+    (hz > 0).then_some(hz)
+}
+
+/// Formats frequency in MHz with at least kHz precision (14.074, 144.3005).
+pub fn format_frequency(frequency_hz :i64) -> String {
+    let mhz = format!("{:.6}", frequency_hz as f64 / 1e6);
+    let (whole, decimals) = mhz.split_once('.').unwrap_or((&mhz, ""));
+    let decimals = decimals.trim_end_matches('0');
+    format!("{}.{:0<3}", whole, decimals)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band_of(text :&str) -> Option<&'static str> {
+        parse_frequency(text).and_then(band_for_frequency).map(|band| band.name)
+    }
+
+    #[test]
+    fn parses_frequency_in_mhz_and_khz() {
+        assert_eq!(parse_frequency("144.300"), Some(144_300_000));
+        assert_eq!(parse_frequency("432"), Some(432_000_000));
+        assert_eq!(parse_frequency("14074"), Some(14_074_000));
+        assert_eq!(parse_frequency("144300"), Some(144_300_000));
+        assert_eq!(parse_frequency("10136"), Some(10_136_000));
+        assert_eq!(parse_frequency(""), None);
+        assert_eq!(parse_frequency("abc"), None);
+        assert_eq!(parse_frequency("-5"), None);
+    }
+
+    #[test]
+    fn parses_microwave_frequency_in_mhz() {
+        assert_eq!(parse_frequency("1296"), Some(1_296_000_000));
+        assert_eq!(band_of("2320"), Some("13cm"));
+        assert_eq!(band_of("3400"), Some("9cm"));
+        assert_eq!(band_of("5760"), Some("6cm"));
+        assert_eq!(band_of("10368"), Some("3cm"));
+        assert_eq!(band_of("24048"), Some("1.25cm"));
+    }
+
+    #[test]
+    fn formats_frequency() {
+        assert_eq!(format_frequency(14_074_000), "14.074");
+        assert_eq!(format_frequency(144_300_500), "144.3005");
+        assert_eq!(format_frequency(1_296_000_000), "1296.000");
+    }
+
+    #[test]
+    fn finds_band_by_name() {
+        assert_eq!(band_by_name(" 2M ").map(|band| band.lower_hz), Some(144_000_000));
+        assert!(band_by_name("3m").is_none());
+    }
+}
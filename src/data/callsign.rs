@@ -0,0 +1,48 @@
+// Helpers for working with callsigns as they are exchanged on air (eg. `DL/OK1ABC/P`).
+
+
+/// Suffixes which only describe way of operation and do not change country of the station.
+const OPERATION_SUFFIXES :[&str; 6] = ["P", "M", "MM", "AM", "QRP", "A"];
+
+/// Returns part of callsign which tells where the station operates from, without suffixes
+/// describing way of operation (ie. `DL/OK1ABC/P` -> `DL`, `OK1ABC/P` -> `OK1ABC`).
+pub fn location_part(call :&str) -> String {
+    let call = call.trim().to_uppercase();
+    let parts :Vec<&str> = call.split('/')
+        .filter(|part| !part.is_empty() && !OPERATION_SUFFIXES.contains(part))
+        .collect();
+
+    // For portable operation the shorter part is prefix of the visited country.
+    let base = match parts.as_slice() {
+        [] => "",
+        [single] => *single,
+        // Only call area change (OK1ABC/2), country stays the same.
+        [first, second, ..] if second.chars().all(|c| c.is_ascii_digit()) => *first,
+        [first, second, ..] => if first.len() <= second.len() { *first } else { *second },
+    };
+    base.to_string()
+}
+
+/// Returns part of callsign which identifies country of operation (ie. `DL/OK1ABC/P` -> `DL`).
+pub fn country_prefix(call :&str) -> String {
+    let base = location_part(call);
+    if base.is_empty() {
+        return base;
+    }
+
+    // Suffix starts with the last letter following a digit (OK1ABC, 9A1AA, 3DA0XX).
+    let bytes = base.as_bytes();
+    let suffix_start = (1..bytes.len())
+        .rev()
+        .find(|i| bytes[*i].is_ascii_alphabetic() && bytes[i - 1].is_ascii_digit());
+
+    let prefix = match suffix_start {
+        Some(index) => &base[..index],
+        None => base.as_str(),
+    };
+    // Last digit is call area (OK1, DL5), digits before it belong to country prefix (S51, 9A1).
+    match prefix.strip_suffix(|c :char| c.is_ascii_digit()) {
+        Some(country) if !country.is_empty() => country.to_string(),
+        _ => prefix.to_string(),
+    }
+}
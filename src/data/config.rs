@@ -1,7 +1,85 @@
-use super::position::Position;
+use crate::database::{macros::{declare_table, define_table_data}, SchemaStep, DBObjectSerializable, DBSchemaObject};
+use super::{data_store::{DataStoreTrait, Describe}, position::{Position, Distance, DistanceFormula, DistanceUnit}};
 
 
+// Decimal places distances are shown with unless user chooses otherwise.
+pub const DEFAULT_DISTANCE_PRECISION :i64 = 2;
+pub const MAX_DISTANCE_PRECISION :i64 = 6;
 
-pub struct ConfigData {
-    pub own_position: Position
-}
\ No newline at end of file
+
+declare_table!(ConfigData,
+    SchemaStep::SQL(
+        "CREATE TABLE ConfigData (
+            id            INTEGER PRIMARY KEY,
+            own_call      TEXT   ,
+            own_locator   TEXT   ,
+            name          TEXT   ,
+            default_power TEXT   ,
+            units         TEXT
+        )"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE ConfigData ADD COLUMN distance_formula TEXT NOT NULL DEFAULT 'sphere'"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE ConfigData ADD COLUMN distance_precision INTEGER NOT NULL DEFAULT 2"
+    )
+);
+
+define_table_data!(ConfigData,
+    (own_call     : String),
+    (own_locator  : String),
+    (name         : String),
+    (default_power: String),
+    (units        : String),
+    (distance_formula: String),
+    (distance_precision: i64)
+);
+
+impl ConfigData {
+    pub fn distance_unit(&self) -> DistanceUnit {
+        DistanceUnit::from_symbol(&self.units).unwrap_or_default()
+    }
+
+    pub fn distance_precision(&self) -> usize {
+        self.distance_precision.clamp(0, MAX_DISTANCE_PRECISION) as usize
+    }
+
+    /// Distance with unit and precision configured by user, like `12.35 km`.
+    pub fn format_distance(&self, distance :&Distance) -> String {
+        distance.format(self.distance_unit(), self.distance_precision())
+    }
+
+    /// Distance between positions by formula chosen by user.
+    pub fn distance(&self, from :&Position, to :&Position) -> Distance {
+        let formula = DistanceFormula::from_name(&self.distance_formula).unwrap_or(DistanceFormula::Sphere);
+        from.distance_by(to, formula)
+    }
+
+    /// Long path distance by formula chosen by user.
+    pub fn long_path_distance(&self, from :&Position, to :&Position) -> Distance {
+        let formula = DistanceFormula::from_name(&self.distance_formula).unwrap_or(DistanceFormula::Sphere);
+        from.long_path_distance_by(to, formula)
+    }
+}
+
+impl Default for ConfigData {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            own_call: "".to_string(),
+            own_locator: "".to_string(),
+            name: "".to_string(),
+            default_power: "".to_string(),
+            units: DistanceUnit::default().symbol().to_string(),
+            distance_formula: DistanceFormula::NAMES[0].to_string(),
+            distance_precision: DEFAULT_DISTANCE_PRECISION,
+        }
+    }
+}
+
+impl Describe for ConfigData {
+    fn describe(&self) -> String {
+        "settings".to_string()
+    }
+}
@@ -0,0 +1,270 @@
+use crate::app_errors::AppError;
+use super::position::Position;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(f64),
+    Letter(char),
+}
+
+/// Splits input into numbers and letters, degree, minute and second marks and commas only separate them.
+fn tokenize(input :&str) -> Result<Vec<Token>, AppError> {
+    let mut tokens = vec!();
+    let mut number = String::new();
+    let flush = |number :&mut String, tokens :&mut Vec<Token>| -> Result<(), AppError> {
+        if !number.is_empty() {
+            let value = number.parse().map_err(|_| AppError::InvalidCoordinates(format!("'{}' is not a number", number)))?;
+            tokens.push(Token::Number(value));
+            number.clear();
+        }
+        Ok(())
+    };
+
+    for character in input.to_uppercase().chars() {
+        match character {
+            '0'..='9' | '.' => number.push(character),
+            '-' | '+' if number.is_empty() => number.push(character),
+            'A'..='Z' => {
+                flush(&mut number, &mut tokens)?;
+                tokens.push(Token::Letter(character));
+            },
+            ' ' | ',' | ';' | '°' | 'º' | '\'' | '"' | '′' | '″' => flush(&mut number, &mut tokens)?,
+            _ => return Err(AppError::InvalidCoordinates(format!("unexpected character '{}'", character))),
+        }
+    }
+    flush(&mut number, &mut tokens)?;
+    Ok(tokens)
+}
+
+
+/// Position of coordinates in decimal degrees, degrees with minutes and seconds, or UTM.
+pub fn parse(input :&str) -> Result<Position, AppError> {
+    let tokens = tokenize(input)?;
+    if let Some(position) = parse_utm(&tokens)? {
+        return Ok(position);
+    }
+    parse_degrees(&tokens)
+}
+
+/// Locator stored for QTH typed by operator, locators are kept as typed and coordinates are converted to 6 character one.
+pub fn to_locator(input :&str) -> Result<String, AppError> {
+    let input = input.trim();
+    if input.is_empty() || Position::parse_qth(input).is_ok() {
+        return Ok(input.to_string());
+    }
+
+    // Input starting with letter and digit is most likely mistyped locator, its error tells more.
+    let looks_like_locator = input.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && input.chars().nth(2).is_some_and(|c| c.is_ascii_digit());
+    match parse(input) {
+        Ok(position) => Ok(position.to_qth()),
+        Err(_) if looks_like_locator => Err(Position::parse_qth(input).unwrap_err().into()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Human readable position like `50.07550°N 14.43780°E`.
+pub fn format(position :&Position) -> String {
+    format!(
+        "{:.5}°{} {:.5}°{}",
+        position.latitude.abs(), if position.latitude < 0.0 { 'S' } else { 'N' },
+        position.longitude.abs(), if position.longitude < 0.0 { 'W' } else { 'E' }
+    )
+}
+
+
+/// Degrees of one coordinate given as degrees, optionally followed by minutes and seconds.
+fn degrees(numbers :&[f64]) -> Result<f64, AppError> {
+    let invalid = |message :&str| AppError::InvalidCoordinates(message.to_string());
+    let (deg, minutes, seconds) = match numbers {
+        [deg] => (*deg, 0.0, 0.0),
+        [deg, minutes] => (*deg, *minutes, 0.0),
+        [deg, minutes, seconds] => (*deg, *minutes, *seconds),
+        [] => return Err(invalid("missing degrees")),
+        _ => return Err(invalid("too many numbers for one coordinate")),
+    };
+    if !(0.0..60.0).contains(&minutes) || !(0.0..60.0).contains(&seconds) {
+        return Err(invalid("minutes and seconds have to be from 0 to 60"));
+    }
+    // Sign of negative degrees applies to minutes and seconds as well.
+    Ok(deg.signum() * (deg.abs() + minutes / 60.0 + seconds / 3600.0))
+}
+
+/// Latitude and longitude, either in this order without hemisphere letters, or in any order with them.
+fn parse_degrees(tokens :&[Token]) -> Result<Position, AppError> {
+    let numbers :Vec<f64> = tokens.iter().filter_map(|t| match t { Token::Number(n) => Some(*n), _ => None }).collect();
+    let letters :Vec<char> = tokens.iter().filter_map(|t| match t { Token::Letter(l) => Some(*l), _ => None }).collect();
+
+    let (latitude, longitude) = if letters.is_empty() {
+        if numbers.is_empty() || !numbers.len().is_multiple_of(2) {
+            return Err(AppError::InvalidCoordinates("expected latitude and longitude".to_string()));
+        }
+        let (lat, lon) = numbers.split_at(numbers.len() / 2);
+        (degrees(lat)?, degrees(lon)?)
+    } else {
+        if letters.len() != 2 {
+            return Err(AppError::InvalidCoordinates("expected one of N/S and one of E/W".to_string()));
+        }
+        // Hemisphere letters are written either before or after numbers of their coordinate.
+        let leading = matches!(tokens.first(), Some(Token::Letter(_)));
+        let mut groups :Vec<(char, Vec<f64>)> = vec!();
+        let mut pending = vec!();
+        for token in tokens {
+            match (token, leading) {
+                (Token::Number(n), true) => match groups.last_mut() {
+                    Some((_, numbers)) => numbers.push(*n),
+                    None => pending.push(*n),
+                },
+                (Token::Number(n), false) => pending.push(*n),
+                (Token::Letter(l), true) => groups.push((*l, vec!())),
+                (Token::Letter(l), false) => groups.push((*l, std::mem::take(&mut pending))),
+            }
+        }
+        if !pending.is_empty() {
+            return Err(AppError::InvalidCoordinates("number without hemisphere letter".to_string()));
+        }
+
+        let mut latitude = None;
+        let mut longitude = None;
+        for (letter, numbers) in groups {
+            let value = degrees(&numbers)?;
+            match letter {
+                'N' => latitude = Some(value),
+                'S' => latitude = Some(-value),
+                'E' => longitude = Some(value),
+                'W' => longitude = Some(-value),
+                _ => return Err(AppError::InvalidCoordinates(format!("unknown hemisphere '{}'", letter))),
+            }
+        }
+        match (latitude, longitude) {
+            (Some(lat), Some(lon)) => (lat, lon),
+            _ => return Err(AppError::InvalidCoordinates("expected one of N/S and one of E/W".to_string())),
+        }
+    };
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(AppError::InvalidCoordinates("latitude has to be within 90 and longitude within 180 degrees".to_string()));
+    }
+    Ok(Position::new(latitude, longitude))
+}
+
+
+/// UTM coordinates written as zone with latitude band, easting and northing, e.g. `33U 458732 5547256`.
+/// Returns None when tokens are not UTM at all.
+fn parse_utm(tokens :&[Token]) -> Result<Option<Position>, AppError> {
+    // Easting and northing can be followed by E and N.
+    let values :Vec<Token> = tokens.iter()
+        .enumerate()
+        .filter(|(index, token)| !(*index > 2 && matches!(token, Token::Letter('E') | Token::Letter('N'))))
+        .map(|(_, token)| *token)
+        .collect();
+    let [Token::Number(zone), Token::Letter(band), Token::Number(easting), Token::Number(northing)] = values[..] else {
+        return Ok(None);
+    };
+    if zone.fract() != 0.0 || !(1.0..=60.0).contains(&zone) {
+        return Ok(None);
+    }
+
+    // Latitude bands go from C in the south to X in the north, I and O are not used.
+    if !('C'..='X').contains(&band) || band == 'I' || band == 'O' {
+        return Err(AppError::InvalidCoordinates(format!("'{}' is not valid UTM latitude band", band)));
+    }
+    // Degrees like `50°N 14°26'E` look like zone and band too, only plausible easting and northing make it UTM.
+    if !(100_000.0..1_000_000.0).contains(&easting) || !(0.0..=10_000_000.0).contains(&northing) {
+        return Ok(None);
+    }
+    Ok(Some(utm_to_position(zone as u32, band >= 'N', easting, northing)))
+}
+
+/// Inverse transverse Mercator projection on WGS-84 ellipsoid.
+fn utm_to_position(zone :u32, northern :bool, easting :f64, northing :f64) -> Position {
+    const A :f64 = 6378137.0;
+    const F :f64 = 1.0 / 298.257223563;
+    const K0 :f64 = 0.9996;
+    let e2 = F * (2.0 - F);
+    let ep2 = e2 / (1.0 - e2);
+
+    let x = easting - 500_000.0;
+    let y = if northern { northing } else { northing - 10_000_000.0 };
+
+    let m = y / K0;
+    let mu = m / (A * (1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1.powi(2) / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let n1 = A / (1.0 - e2 * phi1.sin().powi(2)).sqrt();
+    let t1 = phi1.tan().powi(2);
+    let c1 = ep2 * phi1.cos().powi(2);
+    let r1 = A * (1.0 - e2) / (1.0 - e2 * phi1.sin().powi(2)).powf(1.5);
+    let d = x / (n1 * K0);
+
+    let latitude = phi1 - (n1 * phi1.tan() / r1) * (
+        d.powi(2) / 2.0
+        - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1.powi(2) - 9.0 * ep2) * d.powi(4) / 24.0
+        + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1.powi(2) - 252.0 * ep2 - 3.0 * c1.powi(2)) * d.powi(6) / 720.0
+    );
+    let longitude = (
+        d
+        - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1.powi(2) + 8.0 * ep2 + 24.0 * t1.powi(2)) * d.powi(5) / 120.0
+    ) / phi1.cos();
+
+    let central_meridian = zone as f64 * 6.0 - 183.0;
+    Position::new(latitude.to_degrees(), central_meridian + longitude.to_degrees())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_position(input :&str, latitude :f64, longitude :f64) {
+        let position = parse(input).unwrap_or_else(|err| panic!("{}: {}", input, err));
+        assert!((position.latitude - latitude).abs() < 1e-4, "{}: latitude {}", input, position.latitude);
+        assert!((position.longitude - longitude).abs() < 1e-4, "{}: longitude {}", input, position.longitude);
+    }
+
+    #[test]
+    fn parses_decimal_degrees() {
+        assert_position("50.0755 14.4378", 50.0755, 14.4378);
+        assert_position("-33.8688, 151.2093", -33.8688, 151.2093);
+        assert_position("14.4378E 50.0755N", 50.0755, 14.4378);
+    }
+
+    #[test]
+    fn parses_degrees_minutes_seconds() {
+        assert_position("50°4'31.8\"N 14°26'16.1\"E", 50.0755, 14.437806);
+        assert_position("N 50 4.53 W 14 26.27", 50.0755, -14.437833);
+        assert_position("50°N 14°26'E", 50.0, 14.433333);
+        assert_position("33°S 151°12'E", -33.0, 151.2);
+    }
+
+    #[test]
+    fn parses_utm() {
+        assert_position("56H 334369 6250948", -33.8688, 151.2093);
+        // Published UTM of the Eiffel tower is rounded to tens of meters.
+        let position = parse("31U 448252E 5411935N").unwrap();
+        assert!((position.latitude - 48.85837).abs() < 1e-3 && (position.longitude - 2.29448).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rejects_invalid_coordinates() {
+        assert!(parse("50.0755").is_err());
+        assert!(parse("95 14").is_err());
+        assert!(parse("50 70 14").is_err());
+        assert!(parse("50°N 14°E 3°W").is_err());
+        assert!(parse("33I 458732 5547256").is_err());
+    }
+
+    #[test]
+    fn keeps_locators_and_converts_coordinates() {
+        assert_eq!(to_locator("JO70fd").unwrap(), "JO70fd");
+        assert_eq!(to_locator("50.0755 14.4378").unwrap(), "JO70fb");
+        assert!(matches!(to_locator("JO7Xfd"), Err(AppError::InvalidQTHLocator(_))));
+    }
+}
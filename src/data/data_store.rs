@@ -1,15 +1,65 @@
-use std::{collections::HashMap, cell::{RefCell, RefMut}};
-use crate::database::{Database, DBObjectSerializable, DBSchemaObject};
+use std::{collections::HashMap, cell::{RefCell, RefMut}, sync::atomic::{AtomicU32, Ordering as AtomicOrdering}};
+use serde::Serialize;
 
+use crate::{database::{Database, DBObjectSerializable, DBSchemaObject}, app_errors::AppError};
+use super::history::{History, OPERATION_INSERT, OPERATION_UPDATE, OPERATION_DELETE, OPERATION_RESTORE};
 
 
 
+
+/// Versions are unique across all stores, so reopened logbook never looks like unchanged data
+/// to anything caching by version.
+static VERSION_COUNTER :AtomicU32 = AtomicU32::new(1);
+
+fn next_version() -> u32 {
+    VERSION_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+}
+
+/// Maximum number of changes remembered for undo in each store.
+const UNDO_LIMIT :usize = 500;
+
+
+/// Reversible change of the store content.
+enum Change<T> {
+    Insert(Vec<T>),
+    // Values before and after the change of every edited item.
+    Update(Vec<(T, T)>),
+    // Removed items with their list positions in order of removal.
+    Delete(Vec<(T, usize)>),
+}
+
+impl<T :Describe> Change<T> {
+    fn describe(&self) -> String {
+        match self {
+            Change::Insert(items) if items.len() == 1 => format!("added {}", items[0].describe()),
+            Change::Insert(items) => format!("added {} records", items.len()),
+            Change::Update(items) if items.len() == 1 => format!("edited {}", items[0].1.describe()),
+            Change::Update(items) => format!("edited {} records", items.len()),
+            Change::Delete(removed) if removed.len() == 1 => format!("deleted {}", removed[0].0.describe()),
+            Change::Delete(removed) => format!("deleted {} records", removed.len()),
+        }
+    }
+}
+
+struct ChangeEntry<T> {
+    change: Change<T>,
+    description: String,
+    // Order of the change among all stores.
+    seq: u64,
+    // Latest user change at the time of undo, redo is valid only until next user change.
+    epoch: u64,
+}
+
+
 pub struct DataStore<'a, T> {
     db: &'a RefCell<Database>,
 
     list: Vec<T>,
     map:  HashMap<i64, usize>,
 
+    undo_stack: Vec<ChangeEntry<T>>,
+    redo_stack: Vec<ChangeEntry<T>>,
+
     data_version: u32
 }
 
@@ -18,9 +68,26 @@ pub trait DataStoreTrait {
     fn get_id(&self) -> i64;
 }
 
+/// Short human readable name of the item used in undo and redo messages.
+pub trait Describe {
+    fn describe(&self) -> String;
+}
+
+/// Undo and redo of store changes regardless of the stored type.
+pub trait UndoStore {
+    /// Sequence number of the change undo would revert.
+    fn undo_seq(&self) -> Option<u64>;
+    /// Sequence number of the change redo would apply again.
+    fn redo_seq(&self) -> Option<u64>;
+    /// Reverts last change, returns its description or None when there is nothing to undo.
+    fn undo(&mut self) -> Result<Option<String>, AppError>;
+    /// Applies last undone change again, returns its description or None when there is nothing to redo.
+    fn redo(&mut self) -> Result<Option<String>, AppError>;
+}
+
 
 impl<'a, T> DataStore<'a, T>
-    where T: DBSchemaObject + DBObjectSerializable + DataStoreTrait
+    where T: DBSchemaObject + DBObjectSerializable + DataStoreTrait + Describe + Clone + Serialize
 {
     fn get_db(&mut self) -> RefMut<'_, Database> {
         self.db.borrow_mut()
@@ -38,16 +105,19 @@ impl<'a, T> DataStore<'a, T>
         self.list.len()
     }
 
-    pub fn new(db :&'a RefCell<Database>) -> Result<Self, rusqlite::Error> {
+    pub fn new(db :&'a RefCell<Database>) -> Result<Self, AppError> {
         let mut inst = DataStore {
             db,
             list: Vec::new(),
             map: HashMap::new(),
-            data_version: 1,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            data_version: next_version(),
         };
 
         let mut db = db.borrow_mut();
         db.register_type::<T>()?;
+        db.register_type::<History>()?;
 
         for it in db.select_all::<T>()? {
             inst.add_internal(it);
@@ -56,17 +126,61 @@ impl<'a, T> DataStore<'a, T>
         Ok(inst)
     }
 
-    pub fn edit(&mut self, item :T) -> Result<(), rusqlite::Error> {
-        self.get_db().update(&item)?;
+    /// Records change made by user, it invalidates everything that could be redone.
+    fn record(&mut self, change :Change<T>) {
+        let seq = self.get_db().next_change();
+        self.redo_stack.clear();
+        let description = change.describe();
+        self.undo_stack.push(ChangeEntry { change, description, seq, epoch: seq });
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// Forgets undo history, used for changes user should not be able to take back.
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
 
-        let id = item.get_id();
-        let index = self.map.get(&id).expect("Failed to edit item");
-        self.list[*index] = item;
+    pub fn edit(&mut self, item :T) -> Result<(), AppError> {
+        self.edit_all(vec!(item))
+    }
 
-        self.data_version = self.data_version.wrapping_add(1);
+    /// Edits all items at once, undo reverts them together as one change.
+    pub fn edit_all(&mut self, items :Vec<T>) -> Result<(), AppError> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let before = self.edit_internal(items.clone())?;
+        self.record(Change::Update(before.into_iter().zip(items).collect()));
         Ok(())
     }
 
+    /// Position of the item in the store list.
+    pub fn index_of(&self, id :i64) -> Result<usize, AppError> {
+        self.map.get(&id).copied().ok_or(AppError::MissingRecord { table: T::table_name().to_string(), id })
+    }
+
+    /// Stores items in one transaction and returns their previous values.
+    fn edit_internal(&mut self, items :Vec<T>) -> Result<Vec<T>, AppError> {
+        let indexes = items.iter().map(|item| self.index_of(item.get_id())).collect::<Result<Vec<_>, _>>()?;
+        let history = items.iter().zip(&indexes)
+            .map(|(item, index)| History::new(OPERATION_UPDATE, Some(&self.list[*index]), Some(item)))
+            .collect();
+        self.write_with_history(|db| {
+            for item in &items {
+                db.update(item)?;
+            }
+            Ok(history)
+        })?;
+
+        self.data_version = next_version();
+        Ok(items.into_iter().zip(indexes)
+            .map(|(item, index)| std::mem::replace(&mut self.list[index], item))
+            .collect())
+    }
+
 
     fn add_internal(&mut self, item :T) {
         let item_id = item.get_id();
@@ -75,30 +189,122 @@ impl<'a, T> DataStore<'a, T>
         self.map.insert(item_id, self.list.len() - 1);
     }
 
-    pub fn add(&mut self, item :T) -> Result<(), rusqlite::Error> {
+    pub fn add(&mut self, item :T) -> Result<(), AppError> {
         let mut item = item;
-        self.get_db().insert(&mut item)?;
+        self.write_with_history(|db| {
+            db.insert(&mut item)?;
+            Ok(vec!(History::new(OPERATION_INSERT, None, Some(&item))))
+        })?;
 
+        self.record(Change::Insert(vec!(item.clone())));
         self.add_internal(item);
-        self.data_version = self.data_version.wrapping_add(1);
+        self.data_version = next_version();
         Ok(())
     }
 
-    pub fn remove(&mut self, id :i64) -> Result<(), rusqlite::Error> {
-        let index = self.map.remove(&id).expect("Failed to remove item");
-        let instance = self.list.remove(index);
+    pub fn add_all(&mut self, items :Vec<T>) -> Result<(), AppError> {
+        let mut items = items;
+        self.write_with_history(|db| {
+            db.insert_all(&mut items)?;
+            Ok(items.iter().map(|item| History::new(OPERATION_INSERT, None, Some(item))).collect())
+        })?;
+
+        self.record(Change::Insert(items.clone()));
+        for item in items {
+            self.add_internal(item);
+        }
+        self.data_version = next_version();
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id :i64) -> Result<(), AppError> {
+        let removed = self.remove_internal(id)?;
+        self.record(Change::Delete(vec!(removed)));
+        Ok(())
+    }
+
+    fn remove_internal(&mut self, id :i64) -> Result<(T, usize), AppError> {
+        let index = self.index_of(id)?;
+        let item = &self.list[index];
+        self.write_with_history(|db| {
+            db.delete(item)?;
+            Ok(vec!(History::new(OPERATION_DELETE, Some(item), None)))
+        })?;
+
+        self.map.remove(&id);
+        let item = self.list.remove(index);
+        self.reindex(index);
+
+        self.data_version = next_version();
+        Ok((item, index))
+    }
+
+    /// Puts deleted item back to the database and to its original place in the list.
+    fn restore_internal(&mut self, item :T, index :usize) -> Result<(), AppError> {
+        self.write_with_history(|db| {
+            db.restore(&item)?;
+            Ok(vec!(History::new(OPERATION_RESTORE, None, Some(&item))))
+        })?;
 
-        self.get_db().delete(&instance)?;
+        let index = index.min(self.list.len());
+        self.list.insert(index, item);
+        self.reindex(index);
 
-        self.data_version = self.data_version.wrapping_add(1);
+        self.data_version = next_version();
         Ok(())
     }
 
+    /// Writes rows by `write_rows` together with history entries it returns, previous and new values of
+    /// changed rows, in one transaction. Neither is stored without the other.
+    fn write_with_history(&self, write_rows :impl FnOnce(&mut Database) -> Result<Vec<History>, AppError>) -> Result<(), AppError> {
+        self.db.borrow_mut().in_transaction(|db| {
+            let mut entries = write_rows(db)?;
+            db.insert_all(&mut entries)
+        })
+    }
+
+    fn reindex(&mut self, from :usize) {
+        for (index, item) in self.list.iter().enumerate().skip(from) {
+            self.map.insert(item.get_id(), index);
+        }
+    }
+
+    /// Applies reverse of the change and returns change that reverts it back.
+    fn revert(&mut self, change :Change<T>) -> Result<Change<T>, AppError> {
+        Ok(match change {
+            Change::Insert(items) => {
+                let mut removed = Vec::new();
+                for item in items.iter().rev() {
+                    removed.push(self.remove_internal(item.get_id())?);
+                }
+                Change::Delete(removed)
+            },
+            Change::Update(items) => {
+                let (before, after) :(Vec<T>, Vec<T>) = items.into_iter().unzip();
+                self.edit_internal(before.clone())?;
+                Change::Update(after.into_iter().zip(before).collect())
+            },
+            Change::Delete(removed) => {
+                let mut items = Vec::new();
+                for (item, index) in removed.into_iter().rev() {
+                    self.restore_internal(item.clone(), index)?;
+                    items.push(item);
+                }
+                Change::Insert(items)
+            },
+        })
+    }
+
     pub fn get(&self, id :i64) -> Option<&T> {
         let index = self.map.get(&id)?;
         self.list.get(*index)
     }
 
+    /// Every stored revision of the item, oldest first.
+    pub fn history(&self, id :i64) -> Result<Vec<History>, AppError> {
+        Ok(self.db.borrow_mut().select_where("table_name=?1 AND row_id=?2 ORDER BY time, id", (T::table_name(), id))?)
+    }
+
     pub fn get_where<P>(&mut self, where_clause :&str, params :P) -> Result<Vec<T>, rusqlite::Error>
     where
         P: rusqlite::Params
@@ -109,8 +315,128 @@ impl<'a, T> DataStore<'a, T>
     pub fn get_by_index(&self, index :usize) -> Option<&T> {
         self.list.get(index)
     }
+}
+
+impl<'a, T> UndoStore for DataStore<'a, T>
+    where T: DBSchemaObject + DBObjectSerializable + DataStoreTrait + Describe + Clone + Serialize
+{
+    fn undo_seq(&self) -> Option<u64> {
+        self.undo_stack.last().map(|entry| entry.seq)
+    }
+
+    fn redo_seq(&self) -> Option<u64> {
+        let last_change = self.db.borrow().last_change();
+        self.redo_stack.last()
+            .filter(|entry| entry.epoch == last_change)
+            .map(|entry| entry.seq)
+    }
+
+    fn undo(&mut self) -> Result<Option<String>, AppError> {
+        let Some(entry) = self.undo_stack.pop() else {
+            return Ok(None);
+        };
+        let description = entry.description.clone();
+        match self.revert(entry.change) {
+            Ok(change) => {
+                let epoch = self.db.borrow().last_change();
+                self.redo_stack.push(ChangeEntry { change, epoch, ..entry });
+                Ok(Some(description))
+            },
+            Err(err) => {
+                // Partially reverted change can not be taken back reliably.
+                self.clear_history();
+                Err(err)
+            }
+        }
+    }
+
+    fn redo(&mut self) -> Result<Option<String>, AppError> {
+        if self.redo_seq().is_none() {
+            return Ok(None);
+        }
+        let Some(entry) = self.redo_stack.pop() else {
+            return Ok(None);
+        };
+        let description = entry.description.clone();
+        match self.revert(entry.change) {
+            Ok(change) => {
+                self.undo_stack.push(ChangeEntry { change, ..entry });
+                Ok(Some(description))
+            },
+            Err(err) => {
+                self.clear_history();
+                Err(err)
+            }
+        }
+    }
+}
+
 
-    pub fn find_index_of(&self, id :i64) -> Option<usize> {
-        self.map.get(&id).map(|i| *i)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::LogEntry;
+
+    fn count(db :&RefCell<Database>, table :&str) -> i64 {
+        db.borrow().get_connection().query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn writes_rows_with_history() {
+        let db = RefCell::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let mut logs :DataStore<LogEntry> = DataStore::new(&db).unwrap();
+
+        logs.add(LogEntry { call: "OK1ABC".to_string(), ..Default::default() }).unwrap();
+        let mut log = logs.get_by_index(0).unwrap().clone();
+        log.call = "OK1XYZ".to_string();
+        logs.edit(log).unwrap();
+        assert_eq!(count(&db, "LogEntry"), 1);
+        assert_eq!(count(&db, "History"), 2);
+    }
+
+    #[test]
+    fn stores_nothing_when_history_fails() {
+        let db = RefCell::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let mut logs :DataStore<LogEntry> = DataStore::new(&db).unwrap();
+        logs.add(LogEntry { call: "OK1ABC".to_string(), ..Default::default() }).unwrap();
+        db.borrow().get_connection().execute_batch("DROP TABLE History").unwrap();
+
+        assert!(logs.add(LogEntry { call: "OK2ABC".to_string(), ..Default::default() }).is_err());
+        assert_eq!(count(&db, "LogEntry"), 1);
+        assert_eq!(logs.len(), 1);
+
+        let mut log = logs.get_by_index(0).unwrap().clone();
+        log.call = "OK1XYZ".to_string();
+        assert!(logs.edit(log).is_err());
+        assert_eq!(logs.get_by_index(0).unwrap().call, "OK1ABC");
+        assert_eq!(db.borrow().get_connection().query_row("SELECT call FROM LogEntry", [], |row| row.get::<_, String>(0)).unwrap(), "OK1ABC");
+        assert_eq!(logs.undo_seq(), Some(1));
+    }
+
+    #[test]
+    fn reports_missing_records() {
+        let db = RefCell::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let mut logs :DataStore<LogEntry> = DataStore::new(&db).unwrap();
+        assert!(matches!(logs.remove(42), Err(AppError::MissingRecord { id: 42, .. })));
+        assert!(matches!(logs.edit(LogEntry { id: 42, ..Default::default() }), Err(AppError::MissingRecord { .. })));
+    }
+
+    #[test]
+    fn undoes_station_switch_at_once() {
+        let db = RefCell::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let mut data = crate::data::Data::new(&db).unwrap();
+        for call in ["OK1ABC", "OK1ABC/P"] {
+            data.stations.add(crate::data::StationProfile { call: call.to_string(), ..Default::default() }).unwrap();
+        }
+        let ids :Vec<i64> = data.stations.iter().map(|s| s.id).collect();
+        data.activate_station(ids[0]).unwrap();
+        data.activate_station(ids[1]).unwrap();
+
+        data.undo().unwrap();
+        assert_eq!(data.active_station().map(|s| s.id), Some(ids[0]));
+        assert_eq!(data.stations.iter().filter(|s| s.active).count(), 1);
+        data.redo().unwrap();
+        assert_eq!(data.active_station().map(|s| s.id), Some(ids[1]));
+        assert_eq!(data.stations.iter().filter(|s| s.active).count(), 1);
+    }
+}
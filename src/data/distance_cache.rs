@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use crate::app_context::AppContext;
+use super::position::{Distance, DistanceUnit};
+
+
+/// Distance and azimuth of every log. Resolving own position of each log is too slow to be done
+/// every frame for large logbooks, so values are computed once for each version of the data they depend on.
+#[derive(Default)]
+pub struct DistanceCache {
+    // Versions of logs, races, stations and configuration values were computed from.
+    source: Option<[u32; 4]>,
+    values: HashMap<i64, (Option<Distance>, Option<f64>)>,
+    unit: DistanceUnit,
+}
+
+impl DistanceCache {
+    fn data_versions(app_ctx :&AppContext) -> [u32; 4] {
+        let data = &app_ctx.data;
+        [data.logs.get_version(), data.races.get_version(), data.stations.get_version(), data.configs.get_version()]
+    }
+
+    /// Recomputes values when any data they depend on changed, returns true when it happened.
+    pub fn refresh(&mut self, app_ctx :&AppContext) -> bool {
+        let source = Some(DistanceCache::data_versions(app_ctx));
+        if self.source == source {
+            return false;
+        }
+        self.source = source;
+        self.unit = app_ctx.data.config().distance_unit();
+
+        self.values = app_ctx.data.logs.iter()
+            .map(|log| (log.id, (log.distance(app_ctx), log.azimuth(app_ctx))))
+            .collect();
+        true
+    }
+
+    pub fn distance(&self, log_id :i64) -> Option<Distance> {
+        self.values.get(&log_id).and_then(|(distance, _)| *distance)
+    }
+
+    /// Unit configured by user when distances were computed.
+    pub fn unit(&self) -> DistanceUnit {
+        self.unit
+    }
+
+    pub fn azimuth(&self, log_id :i64) -> Option<f64> {
+        self.values.get(&log_id).and_then(|(_, azimuth)| *azimuth)
+    }
+}
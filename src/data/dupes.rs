@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use crate::app_errors::AppError;
+use super::{Data, LogEntry};
+
+
+pub const DUPE_RULE_HELP :&str = "band, band+mode, race or period=HOURS";
+pub const DEFAULT_DUPE_RULE :&str = "band";
+
+const DAY_SECONDS :i64 = 24 * 3600;
+
+
+/// When working the same call again counts as a duplicate QSO.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DupeRule {
+    // Call can be worked only once in the whole race.
+    Race,
+    Band,
+    BandMode,
+    // Call can be worked again on the same band in every period of given hours, counted from UTC midnight
+    // of each day. Last period of the day is shorter when hours do not divide 24.
+    Period(u32),
+}
+
+impl DupeRule {
+    pub fn parse(definition :&str) -> Result<DupeRule, AppError> {
+        let definition = definition.trim().to_lowercase();
+        let invalid = || AppError::InvalidDupeRule(format!("'{}', use {}", definition, DUPE_RULE_HELP));
+
+        match definition.as_str() {
+            "" => DupeRule::parse(DEFAULT_DUPE_RULE),
+            "race" => Ok(DupeRule::Race),
+            "band" => Ok(DupeRule::Band),
+            "band+mode" => Ok(DupeRule::BandMode),
+            _ => match definition.strip_prefix("period=").and_then(|hours| hours.parse().ok()) {
+                Some(hours) if hours > 0 => Ok(DupeRule::Period(hours)),
+                _ => Err(invalid()),
+            }
+        }
+    }
+
+    /// Rule falling back to the default one when race definition is not valid.
+    pub fn parse_or_default(definition :&str) -> DupeRule {
+        DupeRule::parse(definition).unwrap_or(DupeRule::Band)
+    }
+
+    /// Logs with equal keys are duplicates of each other.
+    fn key(&self, log :&LogEntry) -> String {
+        let call = log.call.trim().to_uppercase();
+        let band = log.band.clone()
+            .or(log.frequency.map(|hz| hz.to_string()))
+            .unwrap_or_default();
+        match self {
+            DupeRule::Race => call,
+            DupeRule::Band => format!("{}|{}", call, band),
+            DupeRule::BandMode => format!("{}|{}|{}", call, band, log.mode.as_deref().unwrap_or_default()),
+            DupeRule::Period(hours) => {
+                let day = log.time.div_euclid(DAY_SECONDS);
+                let period = log.time.rem_euclid(DAY_SECONDS) / (*hours as i64 * 3600);
+                format!("{}|{}|{}|{}", call, band, day, period)
+            },
+        }
+    }
+}
+
+
+/// Finds earlier QSOs of the race a log would duplicate. Logs without race are never duplicates.
+/// Only QSOs made before the log count, so the first QSO of a pair never becomes dupe of the later one.
+#[derive(Default)]
+pub struct DupeChecker {
+    rule: Option<DupeRule>,
+    // Key of every log of the race with id of the earliest log having it.
+    keys: HashMap<String, (i64, i64)>,
+}
+
+impl DupeChecker {
+    /// Log `except` is left out so edited log is not reported as duplicate of itself.
+    pub fn new(data :&Data, race_id :Option<i64>, except :Option<i64>) -> DupeChecker {
+        let Some(race) = race_id.and_then(|id| data.races.get(id)) else {
+            return DupeChecker::default();
+        };
+        let rule = DupeRule::parse_or_default(&race.dupe_rule);
+        DupeChecker::from_logs(rule, data.race_logs(race_id).filter(|log| Some(log.id) != except))
+    }
+
+    fn from_logs<'a>(rule :DupeRule, logs :impl Iterator<Item = &'a LogEntry>) -> DupeChecker {
+        let mut keys :HashMap<String, (i64, i64)> = HashMap::new();
+        for log in logs {
+            let entry = keys.entry(rule.key(log)).or_insert((log.time, log.id));
+            *entry = (*entry).min((log.time, log.id));
+        }
+        DupeChecker { rule: Some(rule), keys }
+    }
+
+    /// Id of the earliest log given one duplicates. Logs made at the same time are ordered by id,
+    /// log not stored yet is the newest one.
+    pub fn find(&self, log :&LogEntry) -> Option<i64> {
+        let rule = self.rule?;
+        if log.call.trim().is_empty() {
+            return None;
+        }
+        let &(time, id) = self.keys.get(&rule.key(log))?;
+        let earlier = time < log.time || (time == log.time && (log.id == 0 || id < log.id));
+        earlier.then_some(id)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(id :i64, call :&str, band :&str, time :i64) -> LogEntry {
+        LogEntry { id, call: call.to_string(), band: Some(band.to_string()), time, ..Default::default() }
+    }
+
+    fn checker(rule :DupeRule, logs :&[LogEntry]) -> DupeChecker {
+        DupeChecker::from_logs(rule, logs.iter())
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!(DupeRule::parse("").unwrap(), DupeRule::Band);
+        assert_eq!(DupeRule::parse("Band+Mode").unwrap(), DupeRule::BandMode);
+        assert_eq!(DupeRule::parse("period=6").unwrap(), DupeRule::Period(6));
+        assert!(DupeRule::parse("period=0").is_err());
+        assert!(DupeRule::parse("bands").is_err());
+    }
+
+    #[test]
+    fn finds_earlier_qso_only() {
+        let first = log(1, "OK1ABC", "2m", 1000);
+        let dupe = log(2, "OK1ABC", "2m", 2000);
+        // First QSO edited while its later dupe is stored is not a dupe of it.
+        let checker = checker(DupeRule::Band, &[dupe]);
+        assert_eq!(checker.find(&first), None);
+        assert_eq!(checker.find(&log(0, "ok1abc", "2m", 3000)), Some(2));
+        assert_eq!(checker.find(&log(0, "OK1ABC", "70cm", 3000)), None);
+    }
+
+    #[test]
+    fn orders_qsos_at_the_same_time_by_id() {
+        let checker = checker(DupeRule::Race, &[log(5, "OK1ABC", "2m", 1000)]);
+        assert_eq!(checker.find(&log(0, "OK1ABC", "70cm", 1000)), Some(5));
+        assert_eq!(checker.find(&log(7, "OK1ABC", "70cm", 1000)), Some(5));
+        assert_eq!(checker.find(&log(3, "OK1ABC", "70cm", 1000)), None);
+    }
+
+    #[test]
+    fn counts_periods_from_midnight() {
+        let rule = DupeRule::Period(5);
+        let day = 19_000 * DAY_SECONDS;
+        // 20:00-24:00 is the last, shorter period, next one starts at midnight.
+        assert_eq!(rule.key(&log(1, "OK1ABC", "2m", day + 21 * 3600)), rule.key(&log(2, "OK1ABC", "2m", day + 23 * 3600)));
+        assert_ne!(rule.key(&log(1, "OK1ABC", "2m", day + 23 * 3600)), rule.key(&log(2, "OK1ABC", "2m", day + 25 * 3600)));
+        assert_ne!(rule.key(&log(1, "OK1ABC", "2m", day + 4 * 3600)), rule.key(&log(2, "OK1ABC", "2m", day + 5 * 3600)));
+    }
+}
@@ -0,0 +1,356 @@
+use std::{collections::HashMap, path::PathBuf, sync::OnceLock};
+
+use crate::{app_errors::AppError, database::Database};
+use super::{callsign, position::Position};
+
+
+/// Country file in format maintained by AD1C at country-files.com, with entities and their prefixes
+/// but without exceptions for single calls. Full `cty.dat` or `cty.csv` placed into application
+/// config directory is used instead of it.
+const BUNDLED_CTY :&str = include_str!("cty.dat");
+const USER_FILES :[&str; 2] = ["cty.dat", "cty.csv"];
+
+static DATABASE :OnceLock<PrefixDatabase> = OnceLock::new();
+
+/// WAE entities whose DXCC entity cannot be found by their own prefix.
+const WAE_PARENTS :[(&str, &str); 1] = [("4U1V", "OE")];
+
+
+/// DXCC entity as listed in the country file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxccEntity {
+    pub name: String,
+    pub primary_prefix: String,
+    // Entity counted only for WAE award (like Sicily), marked by `*` in front of primary prefix.
+    pub wae: bool,
+    // Only `cty.csv` contains DXCC entity numbers.
+    pub dxcc: Option<u32>,
+    pub continent: String,
+    pub cq_zone: u8,
+    pub itu_zone: u8,
+    pub position: Position,
+    // Hours to add to UTC to get local time.
+    pub utc_offset: f64,
+}
+
+/// Entity of a call together with values of the call, which can differ from ones of the whole entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallInfo<'a> {
+    pub entity: &'a DxccEntity,
+    // Differs from `entity` only for WAE entities, which count as part of their DXCC entity.
+    pub dxcc_entity: &'a DxccEntity,
+    pub continent: String,
+    pub cq_zone: u8,
+    pub itu_zone: u8,
+    pub position: Position,
+}
+
+impl CallInfo<'_> {
+    /// Short description like `Czech Republic, EU, CQ 15, ITU 28`.
+    pub fn describe(&self) -> String {
+        format!("{}, {}, CQ {}, ITU {}", self.entity.name, self.continent, self.cq_zone, self.itu_zone)
+    }
+}
+
+
+/// Prefix or exact call with values overriding ones of its entity, e.g. `=OK1ABC(14)[27]`.
+#[derive(Debug, Clone, Default)]
+struct Alias {
+    entity: usize,
+    continent: Option<String>,
+    cq_zone: Option<u8>,
+    itu_zone: Option<u8>,
+    position: Option<Position>,
+}
+
+#[derive(Debug, Default)]
+pub struct PrefixDatabase {
+    entities: Vec<DxccEntity>,
+    // Index of DXCC entity for every entity in `entities`.
+    dxcc_entities: Vec<usize>,
+    prefixes: HashMap<String, Alias>,
+    // Calls which do not follow prefix of their entity, marked with `=` in the file.
+    calls: HashMap<String, Alias>,
+    longest_prefix: usize,
+}
+
+impl PrefixDatabase {
+    /// Parses `cty.dat` format: header line of entity with colon separated values followed by its
+    /// comma separated aliases terminated by semicolon. Longitude and UTC offset are positive to the west.
+    pub fn parse_dat(content :&str) -> Result<PrefixDatabase, AppError> {
+        let mut database = PrefixDatabase::default();
+        let mut rest = content;
+        while !rest.trim().is_empty() {
+            let invalid = |message :&str| AppError::InvalidCountryFile(format!("{} near '{}'", message, rest.trim().lines().next().unwrap_or_default()));
+
+            let fields :Vec<&str> = rest.splitn(9, ':').collect();
+            let [name, cq, itu, continent, lat, lon, offset, prefix, tail] = fields[..] else {
+                return Err(invalid("expected 8 colon separated values"));
+            };
+            let (aliases, remaining) = tail.split_once(';').ok_or_else(|| invalid("missing ';' after aliases"))?;
+
+            let entity = DxccEntity {
+                name: name.trim().to_string(),
+                primary_prefix: prefix.trim().trim_start_matches('*').to_string(),
+                wae: prefix.trim().starts_with('*'),
+                dxcc: None,
+                continent: continent.trim().to_string(),
+                cq_zone: cq.trim().parse().map_err(|_| invalid("invalid CQ zone"))?,
+                itu_zone: itu.trim().parse().map_err(|_| invalid("invalid ITU zone"))?,
+                position: Position::new(
+                    lat.trim().parse().map_err(|_| invalid("invalid latitude"))?,
+                    -lon.trim().parse::<f64>().map_err(|_| invalid("invalid longitude"))?,
+                ),
+                utc_offset: -offset.trim().parse::<f64>().map_err(|_| invalid("invalid UTC offset"))?,
+            };
+            database.add_entity(entity, aliases).map_err(|err| invalid(&err))?;
+            rest = remaining;
+        }
+        database.resolve_dxcc_entities();
+        Ok(database)
+    }
+
+    /// Parses `cty.csv` format: one entity per line with primary prefix, name, DXCC number, continent,
+    /// CQ and ITU zone, latitude and longitude, UTC offset and aliases, again positive to the west.
+    pub fn parse_csv(content :&str) -> Result<PrefixDatabase, AppError> {
+        let mut database = PrefixDatabase::default();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid = |message :&str| AppError::InvalidCountryFile(format!("{} on line '{}'", message, line));
+
+            let fields :Vec<&str> = line.splitn(10, ',').collect();
+            let [prefix, name, dxcc, continent, cq, itu, lat, lon, offset, aliases] = fields[..] else {
+                return Err(invalid("expected 10 comma separated values"));
+            };
+            let entity = DxccEntity {
+                name: name.trim().to_string(),
+                primary_prefix: prefix.trim().trim_start_matches('*').to_string(),
+                wae: prefix.trim().starts_with('*'),
+                dxcc: dxcc.trim().parse().ok(),
+                continent: continent.trim().to_string(),
+                cq_zone: cq.trim().parse().map_err(|_| invalid("invalid CQ zone"))?,
+                itu_zone: itu.trim().parse().map_err(|_| invalid("invalid ITU zone"))?,
+                position: Position::new(
+                    lat.trim().parse().map_err(|_| invalid("invalid latitude"))?,
+                    -lon.trim().parse::<f64>().map_err(|_| invalid("invalid longitude"))?,
+                ),
+                utc_offset: -offset.trim().parse::<f64>().map_err(|_| invalid("invalid UTC offset"))?,
+            };
+            database.add_entity(entity, aliases.trim().trim_end_matches(';')).map_err(|err| invalid(&err))?;
+        }
+        database.resolve_dxcc_entities();
+        Ok(database)
+    }
+
+    fn add_entity(&mut self, entity :DxccEntity, aliases :&str) -> Result<(), String> {
+        let index = self.entities.len();
+        self.entities.push(entity);
+
+        for alias in aliases.split(|c :char| c == ',' || c.is_whitespace()).filter(|a| !a.is_empty()) {
+            let (text, parsed) = parse_alias(alias, index)?;
+            match text.strip_prefix('=') {
+                Some(call) => { self.calls.insert(call.to_string(), parsed); },
+                None => {
+                    self.longest_prefix = self.longest_prefix.max(text.len());
+                    self.prefixes.insert(text, parsed);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn prefix_alias(&self, text :&str, accept :impl Fn(&Alias) -> bool) -> Option<&Alias> {
+        (1..=text.len().min(self.longest_prefix)).rev()
+            .filter(|len| text.is_char_boundary(*len))
+            .filter_map(|len| self.prefixes.get(&text[..len]))
+            .find(|alias| accept(alias))
+    }
+
+    /// WAE entities belong to DXCC entity with their prefix, e.g. `IT9` to `I`, which is the first
+    /// non-WAE entity found by the prefix.
+    fn resolve_dxcc_entities(&mut self) {
+        let dxcc_entities = self.entities.iter().enumerate()
+            .map(|(index, entity)| {
+                if !entity.wae {
+                    return index;
+                }
+                let prefix = entity.primary_prefix.to_uppercase();
+                let prefix = WAE_PARENTS.iter()
+                    .find(|(wae, _)| *wae == prefix)
+                    .map_or(prefix.split('/').next().unwrap_or_default(), |(_, parent)| *parent);
+                self.prefix_alias(prefix, |alias| !self.entities[alias.entity].wae)
+                    .map_or(index, |alias| alias.entity)
+            })
+            .collect();
+        self.dxcc_entities = dxcc_entities;
+    }
+
+    /// Resolves call including portable ones like `DL/OK1ABC/P`, by exact call first and then by the longest matching prefix.
+    pub fn lookup(&self, call :&str) -> Option<CallInfo<'_>> {
+        let call = call.trim().to_uppercase();
+        let location = callsign::location_part(&call);
+
+        let alias = self.calls.get(&call)
+            .or_else(|| self.calls.get(&location))
+            .or_else(|| self.prefix_alias(&location, |_| true))?;
+
+        let entity = &self.entities[alias.entity];
+        Some(CallInfo {
+            entity,
+            dxcc_entity: &self.entities[self.dxcc_entities[alias.entity]],
+            continent: alias.continent.clone().unwrap_or(entity.continent.clone()),
+            cq_zone: alias.cq_zone.unwrap_or(entity.cq_zone),
+            itu_zone: alias.itu_zone.unwrap_or(entity.itu_zone),
+            position: alias.position.unwrap_or(entity.position),
+        })
+    }
+
+    pub fn bundled() -> PrefixDatabase {
+        PrefixDatabase::parse_dat(BUNDLED_CTY).expect("Bundled country file has to be valid")
+    }
+
+    fn user_file() -> Option<PathBuf> {
+        let config_dir = Database::app_dirs().ok()?.config_dir;
+        USER_FILES.iter().map(|name| config_dir.join(name)).find(|path| path.exists())
+    }
+
+    /// Country file from application config directory, None when user did not provide any.
+    pub fn load_user_file() -> Result<Option<PrefixDatabase>, AppError> {
+        let Some(path) = PrefixDatabase::user_file() else {
+            return Ok(None);
+        };
+        let content = std::fs::read_to_string(&path)?;
+        let database = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => PrefixDatabase::parse_csv(&content)?,
+            _ => PrefixDatabase::parse_dat(&content)?,
+        };
+        Ok(Some(database))
+    }
+}
+
+
+/// Splits alias into prefix or `=call` and values overriding ones of the entity:
+/// `(cq)`, `[itu]`, `<lat/lon>`, `{continent}` and `~utc offset~`.
+fn parse_alias(alias :&str, entity :usize) -> Result<(String, Alias), String> {
+    let mut parsed = Alias { entity, ..Default::default() };
+    let mut text = String::new();
+    let mut chars = alias.chars();
+
+    while let Some(character) = chars.next() {
+        let closing = match character {
+            '(' => ')',
+            '[' => ']',
+            '<' => '>',
+            '{' => '}',
+            '~' => '~',
+            _ => {
+                text.push(character);
+                continue;
+            }
+        };
+        let value :String = chars.by_ref().take_while(|c| *c != closing).collect();
+        let invalid = || format!("invalid value '{}' of alias {}", value, alias);
+        match character {
+            '(' => parsed.cq_zone = Some(value.parse().map_err(|_| invalid())?),
+            '[' => parsed.itu_zone = Some(value.parse().map_err(|_| invalid())?),
+            '{' => parsed.continent = Some(value.clone()),
+            '<' => {
+                let (lat, lon) = value.split_once('/').ok_or_else(invalid)?;
+                let lat :f64 = lat.parse().map_err(|_| invalid())?;
+                let lon :f64 = lon.parse().map_err(|_| invalid())?;
+                parsed.position = Some(Position::new(lat, -lon));
+            },
+            // Local time is not used.
+            _ => {},
+        }
+    }
+    Ok((text.to_uppercase(), parsed))
+}
+
+
+/// Loads country file provided by user, bundled one is used when there is none or it is broken.
+pub fn init() -> Result<(), AppError> {
+    if let Some(database) = PrefixDatabase::load_user_file()? {
+        let _ = DATABASE.set(database);
+    }
+    Ok(())
+}
+
+/// Prefix database used by the application, see `init`.
+pub fn database() -> &'static PrefixDatabase {
+    DATABASE.get_or_init(PrefixDatabase::bundled)
+}
+
+/// Identifier of the DXCC entity the call belongs to, used for counting countries.
+/// Calls unknown to the database fall back to their prefix.
+pub fn entity_key(call :&str) -> String {
+    match database().lookup(call) {
+        Some(info) => info.dxcc_entity.primary_prefix.clone(),
+        None => callsign::country_prefix(call),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity_of(database :&PrefixDatabase, call :&str) -> Option<String> {
+        database.lookup(call).map(|info| info.entity.primary_prefix.clone())
+    }
+
+    #[test]
+    fn resolves_calls_by_longest_prefix() {
+        let database = PrefixDatabase::bundled();
+        assert_eq!(entity_of(&database, "OK1ABC"), Some("OK".to_string()));
+        assert_eq!(entity_of(&database, "DL/OK1ABC/P"), Some("DL".to_string()));
+        assert_eq!(entity_of(&database, "KL7/W1AW"), Some("KL".to_string()));
+        assert_eq!(entity_of(&database, "W1AW/KH6"), Some("KH6".to_string()));
+        assert_eq!(entity_of(&database, "RA9ABC"), Some("UA9".to_string()));
+        assert_eq!(entity_of(&database, "OK1ABC/2"), Some("OK".to_string()));
+        assert_eq!(entity_of(&database, "E73A"), Some("E7".to_string()));
+        assert_eq!(entity_of(&database, "Z35T"), Some("Z3".to_string()));
+        assert_eq!(entity_of(&database, "4O3A"), Some("4O".to_string()));
+        assert_eq!(entity_of(&database, "ER1AB"), Some("ER".to_string()));
+        assert_eq!(entity_of(&database, "QQ1ABC"), None);
+    }
+
+    #[test]
+    fn reads_west_positive_values() {
+        let database = PrefixDatabase::bundled();
+        let info = database.lookup("OK1ABC").unwrap();
+        assert_eq!(info.describe(), "Czech Republic, EU, CQ 15, ITU 28");
+        assert!(info.position.longitude > 0.0);
+        assert_eq!(info.entity.utc_offset, 1.0);
+    }
+
+    #[test]
+    fn counts_wae_entities_under_their_dxcc_entity() {
+        let database = PrefixDatabase::bundled();
+        let sicily = database.lookup("IT9ABC").unwrap();
+        assert_eq!(sicily.entity.name, "Sicily");
+        assert!(sicily.entity.wae);
+        assert_eq!(sicily.dxcc_entity.primary_prefix, "I");
+        assert_eq!(database.lookup("TA1AB").unwrap().dxcc_entity.primary_prefix, "TA");
+        assert_eq!(database.lookup("4U1VIC").unwrap().dxcc_entity.primary_prefix, "OE");
+        assert_eq!(database.lookup("IK2ABC").unwrap().dxcc_entity.primary_prefix, "I");
+    }
+
+    #[test]
+    fn parses_csv_with_overrides() {
+        let content = "OK,Czech Republic,503,EU,15,28,50.00,-16.00,-1.0,OK OL =OK1XYZ(14)[27]{AF}<10.0/-20.0>;\n";
+        let database = PrefixDatabase::parse_csv(content).unwrap();
+        assert_eq!(database.lookup("OL5A").unwrap().entity.dxcc, Some(503));
+
+        let info = database.lookup("OK1XYZ").unwrap();
+        assert_eq!((info.cq_zone, info.itu_zone, info.continent.as_str()), (14, 27, "AF"));
+        assert_eq!(info.position, Position::new(10.0, 20.0));
+        assert_eq!(database.lookup("OK1ABC").unwrap().cq_zone, 15);
+    }
+
+    #[test]
+    fn reports_invalid_country_file() {
+        let error = PrefixDatabase::parse_dat("Czech Republic: 15: XX: EU: 50.00: -16.00: -1.0: OK:\n    OK;").unwrap_err();
+        assert_eq!(error.to_string(), AppError::InvalidCountryFile("invalid ITU zone near 'Czech Republic: 15: XX: EU: 50.00: -16.00: -1.0: OK:'".to_string()).to_string());
+        assert!(PrefixDatabase::parse_dat("Czech Republic: 15: 28: EU: 50.00: -16.00: -1.0: OK:\n    OK").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::database::{macros::{declare_table, define_table_data}, SchemaStep, DBObjectSerializable, DBSchemaObject};
+use super::{data_store::DataStoreTrait, qso_time};
+
+
+pub const OPERATION_INSERT :&str = "insert";
+pub const OPERATION_UPDATE :&str = "update";
+pub const OPERATION_DELETE :&str = "delete";
+// Deleted row put back by undo.
+pub const OPERATION_RESTORE :&str = "restore";
+
+
+declare_table!(History,
+    SchemaStep::SQL(
+        "CREATE TABLE History (
+            id          INTEGER PRIMARY KEY,
+            table_name  TEXT    NOT NULL,
+            row_id      INTEGER NOT NULL,
+            operation   TEXT    NOT NULL,
+            time        INTEGER NOT NULL,
+            old_values  TEXT    ,
+            new_values  TEXT
+        )"
+    ),
+    SchemaStep::SQL("CREATE INDEX History_row ON History (table_name, row_id)")
+);
+
+// Values are JSON objects of all row fields, old ones are missing for insert and new ones for delete.
+define_table_data!(History,
+    (table_name : String),
+    (row_id     : i64),
+    (operation  : String),
+    (time       : i64),
+    (old_values : Option<String>),
+    (new_values : Option<String>)
+);
+
+
+impl History {
+    pub fn new<T>(operation :&str, before :Option<&T>, after :Option<&T>) -> Self
+        where T: DBSchemaObject + DataStoreTrait + Serialize
+    {
+        let row_id = before.or(after).map_or(0, |item| item.get_id());
+        Self {
+            id: 0,
+            table_name: T::table_name().to_string(),
+            row_id,
+            operation: operation.to_string(),
+            time: qso_time::now(),
+            old_values: before.and_then(|item| serde_json::to_string(item).ok()),
+            new_values: after.and_then(|item| serde_json::to_string(item).ok()),
+        }
+    }
+
+    fn values(json :&Option<String>) -> Map<String, Value> {
+        json.as_ref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    fn format_value(value :Option<&Value>) -> String {
+        match value {
+            None | Some(Value::Null) => "-".to_string(),
+            Some(Value::String(text)) if text.is_empty() => "''".to_string(),
+            Some(Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+        }
+    }
+
+    /// Fields changed by this revision as (field, old value, new value).
+    pub fn changes(&self) -> Vec<(String, String, String)> {
+        let old = History::values(&self.old_values);
+        let new = History::values(&self.new_values);
+
+        let mut fields :Vec<&String> = new.keys().chain(old.keys().filter(|key| !new.contains_key(*key))).collect();
+        fields.retain(|field| *field != "id" && old.get(*field) != new.get(*field));
+        fields.into_iter()
+            .map(|field| (field.clone(), History::format_value(old.get(field)), History::format_value(new.get(field))))
+            .collect()
+    }
+
+    /// One line summary of the revision for the history view.
+    pub fn summary(&self) -> String {
+        match self.operation.as_str() {
+            OPERATION_INSERT => "created".to_string(),
+            OPERATION_DELETE => "deleted".to_string(),
+            OPERATION_RESTORE => "restored".to_string(),
+            _ => {
+                let changes = self.changes();
+                if changes.is_empty() {
+                    return "no change".to_string();
+                }
+                changes.iter()
+                    .map(|(field, old, new)| format!("{}: {} -> {}", field, old, new))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        }
+    }
+}
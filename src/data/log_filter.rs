@@ -0,0 +1,299 @@
+use std::cmp::Ordering;
+
+use crate::{app_context::AppContext, app_errors::AppError};
+use super::{LogEntry, DistanceCache, qso_time};
+
+
+/// Help text listing supported filter expressions.
+pub const FILTER_HELP :&str = "call:OK1*  loc:JN79*  band:2m  mode:CW  date:2026-10  dist>500  az<90 - conditions separated by space must all match";
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn matches(&self, value :f64, limit :f64) -> bool {
+        match self {
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+            Comparison::Equal => (value - limit).abs() < 0.5,
+            Comparison::GreaterOrEqual => value >= limit,
+            Comparison::Greater => value > limit,
+        }
+    }
+}
+
+
+#[derive(Clone, PartialEq, Debug)]
+enum Condition {
+    Call(String),
+    Locator(String),
+    Band(String),
+    Mode(String),
+    // Prefix of UTC time in `YYYY-MM-DD HH:MM:SS` format.
+    Date(String),
+    // Distance in units configured by user.
+    Distance(Comparison, f64),
+    Azimuth(Comparison, f64),
+}
+
+
+/// Case insensitive match of text against pattern with `*` (any characters) and `?` (one character) wildcards.
+fn wildcard_match(pattern :&str, text :&str) -> bool {
+    let pattern :Vec<char> = pattern.to_uppercase().chars().collect();
+    let text :Vec<char> = text.to_uppercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack :Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, star_t)) = backtrack {
+            p = star + 1;
+            t = star_t + 1;
+            backtrack = Some((star, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+
+/// Filter of logs parsed from expression typed by user, e.g. `call:OK1* dist>500 band:2m`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LogFilter {
+    expression: String,
+    conditions: Vec<Condition>,
+}
+
+impl LogFilter {
+    pub fn parse(expression :&str) -> Result<LogFilter, AppError> {
+        let mut conditions = Vec::new();
+        for token in expression.split_whitespace() {
+            conditions.push(LogFilter::parse_condition(token)?);
+        }
+        Ok(LogFilter {
+            expression: expression.trim().to_string(),
+            conditions,
+        })
+    }
+
+    fn parse_condition(token :&str) -> Result<Condition, AppError> {
+        let invalid = || AppError::InvalidFilter(format!("'{}', expected for example {}", token, FILTER_HELP));
+
+        let split = token.find([':', '<', '>', '=']).ok_or_else(invalid)?;
+        let key = token[..split].to_lowercase();
+        let rest = &token[split..];
+        let (comparison, value) = match rest {
+            _ if rest.starts_with(">=") => (Comparison::GreaterOrEqual, &rest[2..]),
+            _ if rest.starts_with("<=") => (Comparison::LessOrEqual, &rest[2..]),
+            _ if rest.starts_with('>') => (Comparison::Greater, &rest[1..]),
+            _ if rest.starts_with('<') => (Comparison::Less, &rest[1..]),
+            _ => (Comparison::Equal, &rest[1..]),
+        };
+        let number = || value.parse::<f64>().map_err(|_| invalid());
+        let text_only = |condition :Condition| match comparison {
+            Comparison::Equal => Ok(condition),
+            _ => Err(invalid()),
+        };
+
+        match key.as_str() {
+            "call" => text_only(Condition::Call(value.to_string())),
+            "loc" | "qth" | "locator" => text_only(Condition::Locator(value.to_string())),
+            "band" => text_only(Condition::Band(value.to_lowercase())),
+            "mode" => text_only(Condition::Mode(value.to_uppercase())),
+            "date" => text_only(Condition::Date(value.replace('T', " "))),
+            "dist" | "distance" => Ok(Condition::Distance(comparison, number()?)),
+            "az" | "azim" | "azimuth" => Ok(Condition::Azimuth(comparison, number()?)),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    pub fn matches(&self, log :&LogEntry, distances :&DistanceCache) -> bool {
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Call(pattern) => wildcard_match(pattern, &log.call),
+            Condition::Locator(pattern) => wildcard_match(pattern, &log.locator),
+            Condition::Band(band) => log.band.as_deref().unwrap_or_default().to_lowercase() == *band,
+            Condition::Mode(mode) if mode.is_empty() => log.mode.as_deref().unwrap_or_default().is_empty(),
+            Condition::Mode(mode) => [&log.mode, &log.submode].iter()
+                .any(|m| m.as_deref().is_some_and(|m| m.eq_ignore_ascii_case(mode))),
+            Condition::Date(prefix) => qso_time::format_utc(log.time).starts_with(prefix.as_str()),
+            Condition::Distance(comparison, limit) => distances.distance(log.id)
+                .is_some_and(|distance| comparison.matches(distance.in_unit(distances.unit()), *limit)),
+            Condition::Azimuth(comparison, limit) => distances.azimuth(log.id)
+                .is_some_and(|azimuth| comparison.matches(azimuth, *limit)),
+        })
+    }
+}
+
+
+/// Columns log table can be sorted by, in order of their hotkeys `1`-`5`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortColumn {
+    Time,
+    Call,
+    Distance,
+    Azimuth,
+    Locator,
+}
+
+impl SortColumn {
+    pub const ALL :[SortColumn; 5] = [SortColumn::Time, SortColumn::Call, SortColumn::Distance, SortColumn::Azimuth, SortColumn::Locator];
+
+    pub fn from_key(key :char) -> Option<SortColumn> {
+        key.to_digit(10)
+            .and_then(|digit| (digit as usize).checked_sub(1))
+            .and_then(|index| SortColumn::ALL.get(index).copied())
+    }
+
+    pub fn key(&self) -> char {
+        let index = SortColumn::ALL.iter().position(|c| c == self).unwrap_or(0);
+        char::from_digit(index as u32 + 1, 10).unwrap_or('?')
+    }
+}
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LogSort {
+    pub column: SortColumn,
+    pub ascending: bool,
+}
+
+impl LogSort {
+    /// Sorts log ids, logs without distance or azimuth are always placed last.
+    pub fn sort(&self, ids :&mut [i64], app_ctx :&AppContext, distances :&DistanceCache) {
+        let logs = &app_ctx.data.logs;
+        let ascending = self.ascending;
+        let order = |ordering :Ordering| if ascending { ordering } else { ordering.reverse() };
+        let by_number = |a :Option<f64>, b :Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => order(a.total_cmp(&b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+
+        match self.column {
+            SortColumn::Time => ids.sort_by_key(|id| logs.get(*id).map(|log| log.time)),
+            SortColumn::Call => ids.sort_by_cached_key(|id| logs.get(*id).map(|log| log.call.to_uppercase())),
+            SortColumn::Locator => ids.sort_by_cached_key(|id| logs.get(*id).map(|log| log.locator.to_uppercase())),
+            SortColumn::Distance | SortColumn::Azimuth => {
+                let mut keyed :Vec<(Option<f64>, i64)> = ids.iter()
+                    .map(|id| (match self.column {
+                        SortColumn::Distance => distances.distance(*id).map(|distance| distance.meters()),
+                        _ => distances.azimuth(*id),
+                    }, *id))
+                    .collect();
+                keyed.sort_by(|a, b| by_number(a.0, b.0));
+                for (target, (_, id)) in ids.iter_mut().zip(keyed) {
+                    *target = id;
+                }
+                return;
+            },
+        }
+        if !ascending {
+            ids.reverse();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, path::Path};
+
+    use super::*;
+    use crate::{database::Database, data::ConfigData};
+
+    fn log(call :&str, locator :&str, mode :&str) -> LogEntry {
+        LogEntry {
+            call: call.to_string(),
+            locator: locator.to_string(),
+            mode: Some(mode.to_string()),
+            band: Some("2m".to_string()),
+            // 2024-06-01 12:30:00 UTC.
+            time: 1717245000,
+            ..Default::default()
+        }
+    }
+
+    fn matching_calls(app_ctx :&AppContext, distances :&DistanceCache, expression :&str) -> Vec<String> {
+        let filter = LogFilter::parse(expression).unwrap();
+        app_ctx.data.logs.iter()
+            .filter(|log| filter.matches(log, distances))
+            .map(|log| log.call.clone())
+            .collect()
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(wildcard_match("OK1*", "ok1abc"));
+        assert!(wildcard_match("*1A?C", "OK1ABC"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("O*K*", "OKOK"));
+        assert!(!wildcard_match("OK1?", "OK1ABC"));
+        assert!(!wildcard_match("DL*", "OK1ABC"));
+    }
+
+    #[test]
+    fn rejects_invalid_conditions() {
+        for expression in ["OK1ABC", "call>OK1", "dist>far", "colour:red"] {
+            assert!(matches!(LogFilter::parse(expression), Err(AppError::InvalidFilter(_))), "{}", expression);
+        }
+        assert!(LogFilter::parse("  ").unwrap().is_empty());
+        assert_eq!(LogFilter::parse(" call:OK1* ").unwrap().expression(), "call:OK1*");
+    }
+
+    #[test]
+    fn matches_all_conditions() {
+        let db = RefCell::new(Database::new(Path::new(":memory:")).unwrap());
+        let mut app_ctx = AppContext::new(&db).unwrap();
+        app_ctx.data.set_config(ConfigData { own_locator: "JO70fb".to_string(), ..app_ctx.data.config().clone() }).unwrap();
+        app_ctx.data.logs.add_all(vec!(
+            log("OK1ABC", "JO70fb", "CW"),
+            log("DL1XYZ", "JO62qm", "SSB"),
+            LogEntry { submode: Some("FT8".to_string()), ..log("OM3AAA", "JN88nc", "MFSK") },
+        )).unwrap();
+        let mut distances = DistanceCache::default();
+        distances.refresh(&app_ctx);
+
+        let calls = |expression :&str| matching_calls(&app_ctx, &distances, expression);
+        assert_eq!(calls("call:ok1*"), ["OK1ABC"]);
+        assert_eq!(calls("loc:JO*  band:2M"), ["OK1ABC", "DL1XYZ"]);
+        assert_eq!(calls("mode:ft8"), ["OM3AAA"]);
+        assert_eq!(calls("date:2024-06-01T12"), ["OK1ABC", "DL1XYZ", "OM3AAA"]);
+        assert!(calls("date:2024-07").is_empty());
+        assert_eq!(calls("dist>100 dist<=300"), ["DL1XYZ", "OM3AAA"]);
+        assert_eq!(calls("dist=0"), ["OK1ABC"]);
+        // 283.5 km, equality is within half of the unit.
+        assert_eq!(calls("dist=284"), ["DL1XYZ"]);
+        assert!(calls("dist=283").is_empty());
+        assert_eq!(calls("az>=90 az<180"), ["OM3AAA"]);
+
+        // 176.2 and 181.3 miles.
+        app_ctx.data.set_config(ConfigData { units: "mi".to_string(), ..app_ctx.data.config().clone() }).unwrap();
+        distances.refresh(&app_ctx);
+        let calls = |expression :&str| matching_calls(&app_ctx, &distances, expression);
+        assert_eq!(calls("dist>0 dist<180"), ["DL1XYZ"]);
+        assert_eq!(calls("dist>180"), ["OM3AAA"]);
+        assert_eq!(calls("dist=181"), ["OM3AAA"]);
+    }
+}
@@ -1,7 +1,6 @@
-use std::time::{SystemTime, UNIX_EPOCH};
 use ratatui::{widgets::{ListItem, Cell, Row}, style::{Style, Color}, prelude::Constraint};
 use crate::{database::{macros::{declare_table, define_table_data}, SchemaStep, DBObjectSerializable, DBSchemaObject}, app_context::AppContext, app_errors::AppError};
-use super::{Position, data_store::DataStoreTrait};
+use super::{Position, ConfigData, position::Distance, data_store::{DataStoreTrait, Describe}, band, dxcc, qso_time, log_filter::{LogSort, SortColumn}, DistanceCache};
 use rusqlite::Connection;
 
 fn change_location_storage(conn :&Connection) -> Result<(), rusqlite::Error> {
@@ -14,16 +13,15 @@ fn change_location_storage(conn :&Connection) -> Result<(), rusqlite::Error> {
         let id :i64 = row.get(0)?;
         let long :f64 = row.get(1)?;
         let lat :f64 = row.get(2)?;
-        let time :u32 = row.get(3)?;
+        let time :i64 = row.get(3)?;
         let call :String = row.get(4)?;
 
         Ok(LogEntry {
             id: id,
             time: time,
             call: call,
-            code: None,
             locator: Position::new(lat, long).to_qth(),
-            race_id: None,
+            ..Default::default()
         })
     })?;
 
@@ -71,48 +69,158 @@ declare_table!(LogEntry,
     SchemaStep::FN( &|conn :&Connection| change_location_storage(conn) ),
     SchemaStep::SQL(
         "ALTER TABLE LogEntry ADD COLUMN race_id INTEGER"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN notes TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN extra_fields TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN frequency INTEGER"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN band TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN mode TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN submode TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN rst_sent TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN rst_rcvd TEXT"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN serial_sent INTEGER"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN serial_rcvd INTEGER"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN station_id INTEGER"
+    ),
+    // Full text index of logs kept up to date by triggers, see `search` module.
+    SchemaStep::SQL(
+        "CREATE VIRTUAL TABLE LogEntrySearch USING fts5(
+            call, locator, code, notes,
+            content='LogEntry', content_rowid='id'
+        )"
+    ),
+    SchemaStep::SQL(
+        "CREATE TRIGGER LogEntrySearch_insert AFTER INSERT ON LogEntry BEGIN
+            INSERT INTO LogEntrySearch(rowid, call, locator, code, notes) VALUES (new.id, new.call, new.locator, new.code, new.notes);
+        END"
+    ),
+    SchemaStep::SQL(
+        "CREATE TRIGGER LogEntrySearch_delete AFTER DELETE ON LogEntry BEGIN
+            INSERT INTO LogEntrySearch(LogEntrySearch, rowid, call, locator, code, notes) VALUES ('delete', old.id, old.call, old.locator, old.code, old.notes);
+        END"
+    ),
+    SchemaStep::SQL(
+        "CREATE TRIGGER LogEntrySearch_update AFTER UPDATE ON LogEntry BEGIN
+            INSERT INTO LogEntrySearch(LogEntrySearch, rowid, call, locator, code, notes) VALUES ('delete', old.id, old.call, old.locator, old.code, old.notes);
+            INSERT INTO LogEntrySearch(rowid, call, locator, code, notes) VALUES (new.id, new.call, new.locator, new.code, new.notes);
+        END"
+    ),
+    SchemaStep::SQL(
+        "INSERT INTO LogEntrySearch(LogEntrySearch) VALUES ('rebuild')"
+    ),
+    SchemaStep::SQL(
+        "CREATE VIRTUAL TABLE LogEntrySearchVocab USING fts5vocab(LogEntrySearch, 'row')"
+    ),
+    // Duplicate QSO logged on purpose, it is kept in the log but scores no points.
+    SchemaStep::SQL(
+        "ALTER TABLE LogEntry ADD COLUMN dupe INTEGER NOT NULL DEFAULT 0"
     )
 );
 
 define_table_data!(LogEntry,
-    (time   : u32           ),
+    (time   : i64           ),
     (call   : String        ),
     (locator: String        ),
     (code   : Option<String>),
-    (race_id: Option<i64>   )
+    (race_id: Option<i64>   ),
+    (notes  : Option<String>),
+    (extra_fields: Option<String>),
+    (frequency: Option<i64>  ),
+    (band   : Option<String>),
+    (mode   : Option<String>),
+    (submode: Option<String>),
+    (rst_sent: Option<String>),
+    (rst_rcvd: Option<String>),
+    (serial_sent: Option<i64>),
+    (serial_rcvd: Option<i64>),
+    (station_id: Option<i64>),
+    (dupe   : bool          )
 );
 
-static HEADER_CELLS: [&str; 7] = [" Time ", " Call ", " ID ", " Code ", " QTH ", " Dst(km) ", " Azim "];
+static HEADER_CELLS: [&str; 13] = [" Time (UTC) ", " Call ", " ID ", " Code ", " QTH ", " Freq ", " Band ", " Mode ", " RST S/R ", " Nr S/R ", " Dst ", " Azim ", " Entity "];
 impl LogEntry {
-    pub fn table_header() -> Row<'static> {
-        Row::new(HEADER_CELLS.iter()
-            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Red)))
+    /// Local time column is placed right after UTC time when `local_time` is set.
+    /// Sortable columns show their sort key, sorted one also direction of sorting.
+    pub fn table_header(local_time :bool, distance_unit :&str, sort :Option<LogSort>) -> Row<'static> {
+        let mut cells :Vec<String> = HEADER_CELLS.iter()
+            .map(|h| if *h == " Dst " { format!(" Dst({}) ", distance_unit) } else { h.to_string() })
+            .collect();
+        for column in SortColumn::ALL {
+            let index = match column {
+                SortColumn::Time => 0,
+                SortColumn::Call => 1,
+                SortColumn::Locator => 4,
+                SortColumn::Distance => 10,
+                SortColumn::Azimuth => 11,
+            };
+            let direction = match sort {
+                Some(sort) if sort.column == column => if sort.ascending { "^" } else { "v" },
+                _ => "",
+            };
+            cells[index] = format!("{}{}{}", column.key(), cells[index], direction);
+        }
+        if local_time {
+            cells.insert(1, " Local time ".to_string());
+        }
+        Row::new(cells.into_iter()
+            .map(|h| Cell::from(h).style(Style::default().fg(Color::Red)))
         )
     }
 
-    pub fn table_column_constraints() -> &'static [Constraint; 7] {
-        &[
+    pub fn table_column_constraints(local_time :bool) -> Vec<Constraint> {
+        let mut constraints = vec!(
             Constraint::Min(22),
             Constraint::Min(30),
             Constraint::Min(5),
             Constraint::Min(9),
             Constraint::Min(7),
-            Constraint::Min(10),
+            Constraint::Min(11),
+            Constraint::Min(7),
+            Constraint::Min(7),
+            Constraint::Min(9),
+            Constraint::Min(9),
+            Constraint::Min(11),
             Constraint::Min(8),
-        ]
+            Constraint::Min(16),
+        );
+        if local_time {
+            constraints.insert(1, Constraint::Min(22));
+        }
+        constraints
     }
 
-    pub fn table_row(&self, app_ctx :& AppContext) -> Row {
-        let my_position = self.my_position(app_ctx);
-        let cell_time = chrono::NaiveDateTime::from_timestamp_opt(self.time.into(), 0);
-        let cells = [
+    /// Distance and azimuth are taken from cache as resolving them is too slow for every rendered row.
+    pub fn table_row(&self, local_time :bool, distances :&DistanceCache, config :&ConfigData) -> Row {
+        let position = self.position();
+        let mut cells = vec!(
             // TIME
-            cell_time.map_or(
-                Cell::from("Invalid format"),
-                |t| Cell::from(t.to_string())
-            ),
+            Cell::from(qso_time::format_utc(self.time)),
             // CALL
-            Cell::from(self.call.clone()),
+            match self.dupe {
+                true => Cell::from(format!("{} DUPE", self.call)).style(Style::default().fg(Color::DarkGray)),
+                false => Cell::from(self.call.clone()),
+            },
             // ID
             Cell::from(format!("{}", self.get_id())),
             // CODE
@@ -121,49 +229,96 @@ impl LogEntry {
                 |v| Cell::from(v.clone())
             ),
             // QTH
-            self.position().map_or(
+            position.as_ref().map_or(
                 Cell::from("N/A"),
-                |p| Cell::from(p.to_qth())
+                |p| Cell::from(p.to_qth_precision(self.locator.len()))
             ),
-            // DISTANCE
-            self.position().map_or(
+            // FREQUENCY
+            self.frequency.map_or(
                 Cell::from(""),
-                |v| {
-                    let dist = match my_position {
-                        Ok(my_position) => my_position.distance_to(&v),
-                        Err(_) => return Cell::from("N/A")
-                    };
-                    Cell::from(format!("{:.2}", dist.km()))
-                }
+                |v| Cell::from(band::format_frequency(v))
             ),
+            // BAND
+            Cell::from(self.band.clone().unwrap_or_default()),
+            // MODE
+            Cell::from(self.submode.clone().or(self.mode.clone()).unwrap_or_default()),
+            // RST
+            Cell::from(format!(
+                "{}/{}",
+                self.rst_sent.as_deref().unwrap_or("-"),
+                self.rst_rcvd.as_deref().unwrap_or("-")
+            )),
+            // SERIAL NUMBERS
+            Cell::from(format!(
+                "{}/{}",
+                self.serial_sent.map_or("-".to_string(), |v| format!("{:03}", v)),
+                self.serial_rcvd.map_or("-".to_string(), |v| format!("{:03}", v))
+            )),
+            // DISTANCE
+            match (&position, distances.distance(self.id)) {
+                (None, _) => Cell::from(""),
+                (Some(_), None) => Cell::from("N/A"),
+                (Some(_), Some(dist)) => Cell::from(dist.format_value(config.distance_unit(), config.distance_precision())),
+            },
             // AZIMUTH
-            self.position().map_or(
+            match (&position, distances.azimuth(self.id)) {
+                (None, _) => Cell::from(""),
+                (Some(_), None) => Cell::from("N/A"),
+                (Some(_), Some(azim)) => Cell::from(format!("{:.1}", azim)),
+            },
+            // ENTITY
+            dxcc::database().lookup(&self.call).map_or(
                 Cell::from(""),
-                |v| {
-                    let azim = match my_position {
-                        Ok(my_position) => my_position.azimuth_to(&v),
-                        Err(_) => return Cell::from("N/A")
-                    };
-                    Cell::from(format!("{:.1}", azim))
-                }
+                |info| Cell::from(info.entity.name.clone())
             )
-        ];
+        );
+        if local_time {
+            cells.insert(1, Cell::from(qso_time::format_local(self.time)));
+        }
         Row::new(cells).height(1)
     }
 
+    /// Sets frequency together with band derived from it. Band is kept when frequency is outside of known bands.
+    pub fn set_frequency(&mut self, frequency :Option<i64>) {
+        self.frequency = frequency;
+        if let Some(band) = frequency.and_then(band::band_for_frequency) {
+            self.band = Some(band.name.to_string());
+        }
+    }
+
+    /// Signal report which is usually given for given mode when operator does not provide any.
+    pub fn default_report(mode :&str) -> &'static str {
+        match mode.to_uppercase().as_str() {
+            "CW" | "RTTY" | "PSK31" | "PSK" => "599",
+            "FT8" | "FT4" | "JT65" | "JT9" | "MSK144" | "Q65" | "MFSK" => "-10",
+            _ => "59",
+        }
+    }
+
     pub fn position(&self) -> Option<Position> {
         Position::from_qth(&self.locator).ok()
     }
 
     pub fn my_position(&self, app_ctx :&AppContext) -> Result<Position, AppError> {
-        match self.race_id {
-            Some(race_id) => app_ctx.data.races.get(race_id).map_or(
-                Ok(app_ctx.data.config.own_position),
-                |v| Position::from_qth(&v.my_location).or(Err(AppError::InvalidQTHLocator))
-            ),
-            None => Ok(app_ctx.data.config.own_position)
-        }
+        app_ctx.data.resolve_position(self.station_id, self.race_id)
+    }
+
+    /// Distance to the station, None when any of positions is unknown.
+    pub fn distance(&self, app_ctx :&AppContext) -> Option<Distance> {
+        let position = self.position()?;
+        Some(app_ctx.data.config().distance(&self.my_position(app_ctx).ok()?, &position))
+    }
+
+    /// How much the distance can differ from the real one as both locators cover a square.
+    pub fn distance_uncertainty(&self, app_ctx :&AppContext) -> Option<Distance> {
+        let own = Position::locator_uncertainty(app_ctx.data.resolve_locator(self.station_id, self.race_id)).ok()?;
+        let other = Position::locator_uncertainty(&self.locator).ok()?;
+        Some(own + other)
+    }
 
+    pub fn azimuth(&self, app_ctx :&AppContext) -> Option<f64> {
+        let position = self.position()?;
+        Some(self.my_position(app_ctx).ok()?.azimuth_to(&position))
     }
 }
 
@@ -172,12 +327,24 @@ impl LogEntry {
 impl Default for LogEntry {
     fn default() -> Self {
         LogEntry {
-            time: SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backward").as_secs() as u32,
+            time: qso_time::now(),
             call: "".to_string(),
             code: None,
             locator: "".to_string(),
             id: 0,
             race_id: None,
+            notes: None,
+            extra_fields: None,
+            frequency: None,
+            band: None,
+            mode: None,
+            submode: None,
+            rst_sent: None,
+            rst_rcvd: None,
+            serial_sent: None,
+            serial_rcvd: None,
+            station_id: None,
+            dupe: false,
         }
     }
 }
@@ -187,3 +354,9 @@ impl<'a> Into<ListItem<'a>> for LogEntry {
         ListItem::new(self.call)
     }
 }
+
+impl Describe for LogEntry {
+    fn describe(&self) -> String {
+        format!("log {}", self.call)
+    }
+}
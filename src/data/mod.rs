@@ -1,9 +1,14 @@
 use std::cell::RefCell;
 
 mod config;
-use config::ConfigData;
+pub use config::{ConfigData, MAX_DISTANCE_PRECISION};
 
 pub mod position;
+pub mod coordinates;
+pub mod callsign;
+pub mod dxcc;
+pub mod band;
+pub mod qso_time;
 use position::Position;
 
 
@@ -13,8 +18,24 @@ pub use logs::LogEntry;
 mod races;
 pub use races::Race;
 
+pub mod log_filter;
+pub mod search;
+pub mod stats;
+pub mod scoring;
+pub mod dupes;
+use scoring::{Score, ScoringRules};
+
+mod distance_cache;
+pub use distance_cache::DistanceCache;
+
+mod history;
+pub use history::History;
+
+mod stations;
+pub use stations::StationProfile;
+
 mod data_store;
-use data_store::DataStore;
+use data_store::{DataStore, UndoStore};
 
 use crate::{database::Database, app_errors::AppError};
 
@@ -22,36 +43,216 @@ use crate::{database::Database, app_errors::AppError};
 pub struct Data<'a> {
     pub logs: DataStore<'a, LogEntry>,
     pub races: DataStore<'a, Race>,
+    pub stations: DataStore<'a, StationProfile>,
 
-    pub config: ConfigData,
+    pub configs: DataStore<'a, ConfigData>,
+    default_config: ConfigData,
 
     pub current_race_id: Option<i64>,
 }
 
 impl<'a> Data<'a> {
     pub fn new(db :&'a RefCell<Database>) -> Result<Self, AppError> {
+        {
+            let db = db.borrow();
+            db.check_schema::<ConfigData>()?;
+            db.check_schema::<Race>()?;
+            db.check_schema::<StationProfile>()?;
+            db.check_schema::<LogEntry>()?;
+            db.check_schema::<History>()?;
+        }
+
+        // Read-only logbook without stored configuration works with defaults.
+        let mut configs = DataStore::new(db)?;
+        if configs.len() == 0 && !db.borrow().is_read_only() {
+            configs.add(ConfigData::default())?;
+            configs.clear_history();
+        }
+
         Ok(Self {
             races: DataStore::new(db)?,
+            stations: DataStore::new(db)?,
             logs: DataStore::new(db)?,
 
-            config: ConfigData {
-                own_position: Position::new(50.061520, 14.091540)
-            },
+            configs,
+            default_config: ConfigData::default(),
 
             current_race_id: None,
         })
     }
 
+    /// Station configuration, there is always exactly one stored unless logbook is read-only.
+    pub fn config(&self) -> &ConfigData {
+        self.configs.get_by_index(0).unwrap_or(&self.default_config)
+    }
+
+    pub fn set_config(&mut self, config :ConfigData) -> Result<(), AppError> {
+        match self.configs.len() {
+            0 => self.configs.add(config),
+            _ => self.configs.edit(config),
+        }
+    }
+
     pub fn race_logs(&self, race_id :Option<i64>) -> impl Iterator<Item = &LogEntry> {
         self.logs.iter().filter(move |v| race_id.is_none() || (*v).race_id == race_id)
     }
 
-    pub fn my_position(&self) -> Position {
-        match self.current_race_id {
-            Some(id) => self.races.get(id).map(
-                |r| Position::from_qth(&r.my_location)
-            ).map_or(self.config.own_position, |v| v.unwrap()),
-            None => self.config.own_position
+    /// Serial number to send in next QSO of the race. Serials restart in every race, logs without
+    /// race are numbered on their own.
+    pub fn next_serial(&self, race_id :Option<i64>) -> i64 {
+        self.logs.iter()
+            .filter(|log| log.race_id == race_id)
+            .filter_map(|log| log.serial_sent)
+            .max().unwrap_or(0) + 1
+    }
+
+    pub fn active_station(&self) -> Option<&StationProfile> {
+        self.stations.iter().find(|station| station.active)
+    }
+
+    /// Marks given station profile as the active one, all others are deactivated.
+    pub fn activate_station(&mut self, station_id :i64) -> Result<(), AppError> {
+        let changed :Vec<StationProfile> = self.stations.iter()
+            .filter(|station| station.active != (station.id == station_id))
+            .map(|station| StationProfile { active: station.id == station_id, ..station.clone() })
+            .collect();
+
+        // One change, so undo never leaves two active stations or none.
+        self.stations.edit_all(changed)
+    }
+
+    /// Station profile which new logs of given race are made from: the race one, or the active one.
+    pub fn current_station_id(&self, race_id :Option<i64>) -> Option<i64> {
+        race_id.and_then(|id| self.races.get(id))
+            .and_then(|race| race.station_id)
+            .or(self.active_station().map(|station| station.id))
+    }
+
+    /// Station profile of log, falling back to profile of its race when log does not reference any.
+    pub fn station_of(&self, station_id :Option<i64>, race_id :Option<i64>) -> Option<&StationProfile> {
+        station_id
+            .or(race_id.and_then(|id| self.races.get(id)).and_then(|race| race.station_id))
+            .and_then(|id| self.stations.get(id))
+    }
+
+    /// Locator logs are made from. Location typed into the race wins, as it is usually a portable site
+    /// the race is operated from. Then station profile of the log or race, active station profile and
+    /// finally configured own locator.
+    pub fn resolve_locator(&self, station_id :Option<i64>, race_id :Option<i64>) -> &str {
+        if let Some(race) = race_id.and_then(|id| self.races.get(id)).filter(|race| !race.my_location.is_empty()) {
+            return &race.my_location;
+        }
+
+        let with_locator = |station :&&StationProfile| !station.locator.is_empty();
+        match self.station_of(station_id, race_id).filter(with_locator).or(self.active_station().filter(with_locator)) {
+            Some(station) => &station.locator,
+            None => &self.config().own_locator
+        }
+    }
+
+    /// Call logs are made with, resolved the same way as `resolve_locator`.
+    pub fn resolve_call(&self, station_id :Option<i64>, race_id :Option<i64>) -> &str {
+        if let Some(race) = race_id.and_then(|id| self.races.get(id)).filter(|race| !race.my_call.is_empty()) {
+            return &race.my_call;
+        }
+
+        let with_call = |station :&&StationProfile| !station.call.is_empty();
+        match self.station_of(station_id, race_id).filter(with_call).or(self.active_station().filter(with_call)) {
+            Some(station) => &station.call,
+            None => &self.config().own_call
         }
     }
-}
\ No newline at end of file
+
+    /// Position logs are made from, see `resolve_locator`.
+    pub fn resolve_position(&self, station_id :Option<i64>, race_id :Option<i64>) -> Result<Position, AppError> {
+        match self.resolve_locator(station_id, race_id) {
+            "" => Err(AppError::MissingOwnLocator),
+            locator => Position::from_qth(locator),
+        }
+    }
+
+    /// Distance of log in kilometers, contest rules count kilometers regardless of units configured by user.
+    pub fn log_distance_km(&self, log :&LogEntry) -> Option<f64> {
+        let position = log.position()?;
+        Some(self.config().distance(&self.resolve_position(log.station_id, log.race_id).ok()?, &position).km())
+    }
+
+    /// Scoring rules of the race, logs without race are scored by default ones.
+    pub fn scoring_rules(&self, race_id :Option<i64>) -> ScoringRules {
+        let definition = race_id.and_then(|id| self.races.get(id)).map_or("", |race| race.scoring.as_str());
+        ScoringRules::parse_or_default(definition)
+    }
+
+    /// Claimed score of the race, computed from all its logs.
+    pub fn race_score(&self, race_id :Option<i64>) -> Score {
+        let rules = self.scoring_rules(race_id);
+        let mut score = Score::new(&rules);
+        for log in self.race_logs(race_id) {
+            score.add(&rules, log, self.log_distance_km(log));
+        }
+        score
+    }
+
+    pub fn my_position(&self) -> Result<Position, AppError> {
+        self.resolve_position(None, self.current_race_id)
+    }
+
+    fn undo_stores(&mut self) -> [&mut dyn UndoStore; 4] {
+        [&mut self.logs, &mut self.races, &mut self.stations, &mut self.configs]
+    }
+
+    /// Reverts the latest change across all stores. Returns description of the reverted change,
+    /// None when there is nothing to undo.
+    pub fn undo(&mut self) -> Result<Option<String>, AppError> {
+        let store = self.undo_stores().into_iter()
+            .filter(|store| store.undo_seq().is_some())
+            .max_by_key(|store| store.undo_seq());
+        match store {
+            Some(store) => store.undo(),
+            None => Ok(None),
+        }
+    }
+
+    /// Applies again the most recently undone change, possible only until next change is made.
+    pub fn redo(&mut self) -> Result<Option<String>, AppError> {
+        let store = self.undo_stores().into_iter()
+            .filter(|store| store.redo_seq().is_some())
+            .min_by_key(|store| store.redo_seq());
+        match store {
+            Some(store) => store.redo(),
+            None => Ok(None),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_serials_per_race() {
+        let db = RefCell::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        let mut data = Data::new(&db).unwrap();
+        data.logs.add_all(vec!(
+            LogEntry { race_id: Some(1), serial_sent: Some(7), ..Default::default() },
+            LogEntry { race_id: Some(2), serial_sent: Some(3), ..Default::default() },
+        )).unwrap();
+
+        assert_eq!(data.next_serial(Some(1)), 8);
+        assert_eq!(data.next_serial(Some(2)), 4);
+        assert_eq!(data.next_serial(Some(3)), 1);
+        assert_eq!(data.next_serial(None), 1);
+    }
+
+    #[test]
+    fn migrates_nothing_when_some_table_is_too_new() {
+        let db = RefCell::new(Database::new(std::path::Path::new(":memory:")).unwrap());
+        db.borrow().get_connection().execute("INSERT INTO TableDescriptor VALUES ('LogEntry', 999)", []).unwrap();
+
+        assert!(matches!(Data::new(&db), Err(AppError::SchemaTooNew { found: 999, .. })));
+        let tables :i64 = db.borrow().get_connection()
+            .query_row("SELECT COUNT(*) FROM TableDescriptor", [], |row| row.get(0)).unwrap();
+        assert_eq!(tables, 2);
+    }
+}
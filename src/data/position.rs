@@ -1,5 +1,66 @@
 use crate::app_errors::AppError;
 
+
+/// Reason why locator could not be parsed, positions of characters start with 1.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum LocatorError {
+    #[error("locator has to have 2, 4, 6, 8 or 10 characters, got {0}")]
+    InvalidLength(usize),
+
+    #[error("character '{character}' at position {position} is not valid, expected {expected}")]
+    InvalidCharacter { position: usize, character: char, expected: &'static str },
+}
+
+const FIELD_CHARS :&str = "ABCDEFGHIJKLMNOPQR";
+const SUBSQUARE_CHARS :&str = "ABCDEFGHIJKLMNOPQRSTUVWX";
+const DIGITS :&str = "0123456789";
+
+// Characters allowed in every pair of locator and degrees of longitude and latitude one step of the pair covers.
+const LOCATOR_PAIRS :[(&str, &str, f64, f64); 5] = [
+    ("letter A-R", FIELD_CHARS, 20.0, 10.0),                            // Field.
+    ("digit", DIGITS, 2.0, 1.0),                                        // Square.
+    ("letter A-X", SUBSQUARE_CHARS, 2.0 / 24.0, 1.0 / 24.0),            // Subsquare, 5 x 2.5 minutes.
+    ("digit", DIGITS, 2.0 / 240.0, 1.0 / 240.0),                        // Extended square, 30 x 15 seconds.
+    ("letter A-X", SUBSQUARE_CHARS, 2.0 / 5760.0, 1.0 / 5760.0),        // Extended subsquare.
+];
+
+
+// Mean radius of Earth used by spherical formulas.
+const EARTH_RADIUS :f64 = 6371e3;
+// Semi-major axis and flattening of WGS-84 ellipsoid.
+const WGS84_A :f64 = 6378137.0;
+const WGS84_F :f64 = 1.0 / 298.257223563;
+
+
+/// How distances between positions are computed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceFormula {
+    // Haversine on sphere, used by most contest organisers.
+    Sphere,
+    // Vincenty inverse formula on WGS-84 ellipsoid, precise to millimeters.
+    Wgs84,
+}
+
+impl DistanceFormula {
+    pub const NAMES :[&'static str; 2] = ["sphere", "wgs84"];
+
+    pub fn from_name(name :&str) -> Option<DistanceFormula> {
+        match name {
+            "sphere" => Some(DistanceFormula::Sphere),
+            "wgs84" => Some(DistanceFormula::Wgs84),
+            _ => None,
+        }
+    }
+}
+
+
+/// Size of the square covered by locator, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SquareSize {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Position {
     pub latitude: f64,
@@ -16,82 +77,81 @@ impl Position {
         }
     }
 
+    /// Center of the square given locator of 2, 4, 6, 8 or 10 characters covers.
     pub fn from_qth(qth: &str) -> Result<Position, AppError> {
-        if qth.len() < 4 || qth.len() > 6 {
-            return Err(AppError::InvalidQTHLocator);
-        }
-        let qth = qth.to_uppercase();
-
-        // Constants.
-        const STR_CHR_UP :&str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-        const STR_NUM :&str = "0123456789";
-
-        // Calculate lat/lon.
-        // 2nd digit: 10deg latitude slot.
-        let lat_letter = &qth[1..2];
-        let lat_index = STR_CHR_UP.find( lat_letter ).ok_or( AppError::InvalidQTHLocator)?;
-        let mut lat = (lat_index * 10) as f64;
-
-
-        // 1st digit: 20deg longitude slot.
-        let lon_letter = &qth[0..1];
-        let lon_index = STR_CHR_UP.find( lon_letter ).ok_or( AppError::InvalidQTHLocator)?;
-        let mut lon = (lon_index * 20) as f64;
-
-        lat += (STR_NUM.find( &qth[3..4]).ok_or(AppError::InvalidQTHLocator)? * 1) as f64;  // 4th digit: 1deg latitude slot.
-        lon += (STR_NUM.find( &qth[2..3]).ok_or(AppError::InvalidQTHLocator)? * 2) as f64;  // 3rd digit: 2deg longitude slot.
+        Ok(Position::parse_qth(qth)?.0)
+    }
 
-        if qth.len() == 6 {
-            lat += (STR_CHR_UP.find(&qth[5..6]).ok_or(AppError::InvalidQTHLocator)? as f64) * 2.5 / 60.0;   // 6th digit: 2.5min latitude slot.
-            lon += (STR_CHR_UP.find(&qth[4..5]).ok_or(AppError::InvalidQTHLocator)? as f64) * 5.0 / 60.0;   // 5th digit: 5min longitude slot.
+    /// Center of the locator square together with its size.
+    pub fn parse_qth(qth: &str) -> Result<(Position, SquareSize), LocatorError> {
+        let chars :Vec<char> = qth.to_uppercase().chars().collect();
+        if chars.is_empty() || !chars.len().is_multiple_of(2) || chars.len() > LOCATOR_PAIRS.len() * 2 {
+            return Err(LocatorError::InvalidLength(chars.len()));
         }
 
-        if qth.len() == 4 {
-            // Get coordinates of the center of the square.
-            lat += 0.5 * 1.0;
-            lon += 0.5 * 2.0;
-        }
-        else {
-            lat += 0.5 * 2.5 / 60.0;
-            lon += 0.5 * 5.0 / 60.0;
+        // Locator lat/lon origin is shifted to the south pole and antimeridian.
+        let mut lat = 0.0;
+        let mut lon = 0.0;
+        let mut size = SquareSize { latitude: 180.0, longitude: 360.0 };
+        for (index, pair) in chars.chunks(2).enumerate() {
+            let (expected, allowed, lon_step, lat_step) = LOCATOR_PAIRS[index];
+            let value = |offset :usize| allowed.find(pair[offset])
+                .map(|value| value as f64)
+                .ok_or(LocatorError::InvalidCharacter { position: index * 2 + offset + 1, character: pair[offset], expected });
+
+            // Longitude goes first in every pair.
+            lon += value(0)? * lon_step;
+            lat += value(1)? * lat_step;
+            size = SquareSize { latitude: lat_step, longitude: lon_step };
         }
 
-        // Locator lat/lon origin shift.
-        lat -= 90.0;
-        lon -= 180.0;
-
-        Ok(Position {
-            latitude: lat,
-            longitude: lon,
-        })
+        // Get coordinates of the center of the square.
+        let position = Position {
+            latitude: lat + size.latitude / 2.0 - 90.0,
+            longitude: lon + size.longitude / 2.0 - 180.0,
+        };
+        Ok((position, size))
     }
 
+    /// Locator of 6 characters, precise to about 5 km.
     pub fn to_qth(&self) -> String {
+        self.to_qth_precision(6)
+    }
+
+    /// Locator of given length, 2, 4, 6, 8 or 10 characters. Odd length is rounded down.
+    pub fn to_qth_precision(self, length :usize) -> String {
         if (self.latitude < -90.0 || self.latitude > 90.0) || (self.longitude < -180.0 || self.longitude > 180.0) {
             return String::from("INVALID");
         }
 
-        // Normalize to positive values.
-        let lat = self.latitude + 90.0;
-        let lon = self.longitude + 180.0;
-
-        // Constants.
-        const STR_CHR_UP :&str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-        const STR_CHR_LO :&str = "abcdefghijklmnopqrstuvwxyz";
-        const STR_NUM :&str = "0123456789";
+        // Normalize to positive values, poles and antimeridian fall into the last squares.
+        let mut lat = (self.latitude + 90.0).min(180.0 - 1e-9);
+        let mut lon = (self.longitude + 180.0).min(360.0 - 1e-9);
 
-        // Calculate QTH locator.
         let mut qth = String::new();
-        qth.push(STR_CHR_UP.as_bytes()[(lon / 20.0).floor() as usize] as char); // 1st digit: 20deg longitude slot.
-        qth.push(STR_CHR_UP.as_bytes()[(lat / 10.0).floor() as usize] as char); // 2nd digit: 10deg latitude slot.
-        qth.push(STR_NUM.as_bytes()[((lon % 20.0) / 2.0).floor() as usize] as char); // 3rd digit: 2deg longitude slot.
-        qth.push(STR_NUM.as_bytes()[((lat % 10.0) / 1.0).floor() as usize] as char); // 4th digit: 1deg latitude slot.
-        qth.push(STR_CHR_LO.as_bytes()[((lon % 2.0) * (60.0 / 5.0)).floor() as usize] as char); // 5th digit: 5min longitude slot.
-        qth.push(STR_CHR_LO.as_bytes()[((lat % 1.0) * (60.0 / 2.5)).floor() as usize] as char); // 6th digit: 2.5min latitude slot.
+        for (index, (_, allowed, lon_step, lat_step)) in LOCATOR_PAIRS.iter().take((length / 2).clamp(1, LOCATOR_PAIRS.len())).enumerate() {
+            let lon_index = ((lon / lon_step).floor() as usize).min(allowed.len() - 1);
+            let lat_index = ((lat / lat_step).floor() as usize).min(allowed.len() - 1);
+            lon = (lon - lon_index as f64 * lon_step).max(0.0);
+            lat = (lat - lat_index as f64 * lat_step).max(0.0);
+
+            // Subsquares are written in lower case, e.g. JN79fx12.
+            for char_index in [lon_index, lat_index] {
+                let character = allowed.as_bytes()[char_index] as char;
+                qth.push(if index % 2 == 0 && index > 0 { character.to_ascii_lowercase() } else { character });
+            }
+        }
 
         return qth;
     }
 
+    /// Largest distance of any point of the locator square from its center.
+    pub fn locator_uncertainty(qth: &str) -> Result<Distance, LocatorError> {
+        let (center, size) = Position::parse_qth(qth)?;
+        let corner = Position::new(center.latitude - size.latitude / 2.0, center.longitude - size.longitude / 2.0);
+        Ok(center.distance_to(&corner))
+    }
+
     /// Implementation of Haversine distance between two points.
     pub fn distance_to(&self, end: &Position) -> Distance {
         let haversine_fn = |theta: f64| (1.0 - theta.cos()) / 2.0;
@@ -105,7 +165,105 @@ impl Position {
         let hav_delta_lambda = phi1.cos() * phi2.cos() * haversine_fn(lambda2 - lambda1);
         let total_delta = hav_delta_phi + hav_delta_lambda;
 
-        Distance::from_meters((2.0 * 6371e3 * total_delta.sqrt().asin() * 1000.0).round() / 1000.0)
+        Distance::from_meters((2.0 * EARTH_RADIUS * total_delta.sqrt().asin() * 1000.0).round() / 1000.0)
+    }
+
+    /// Distance by given formula. Ellipsoidal one falls back to spherical for nearly antipodal points it does not converge for.
+    pub fn distance_by(&self, end: &Position, formula: DistanceFormula) -> Distance {
+        match formula {
+            DistanceFormula::Sphere => self.distance_to(end),
+            DistanceFormula::Wgs84 => self.ellipsoid_distance_to(end).unwrap_or_else(|| self.distance_to(end)),
+        }
+    }
+
+    /// Distance going the other way around the Earth.
+    pub fn long_path_distance_to(&self, end: &Position) -> Distance {
+        let circumference = 2.0 * std::f64::consts::PI * EARTH_RADIUS;
+        Distance::from_meters(((circumference - self.distance_to(end).meters) * 1000.0).round() / 1000.0)
+    }
+
+    /// Long path by given formula, so it adds up with the short path of the same formula.
+    pub fn long_path_distance_by(&self, end: &Position, formula: DistanceFormula) -> Distance {
+        match (formula, self.great_ellipse_circumference(end)) {
+            (DistanceFormula::Wgs84, Some(circumference)) => {
+                let short = self.distance_by(end, formula).meters;
+                Distance::from_meters(((circumference - short) * 1000.0).round() / 1000.0)
+            },
+            _ => self.long_path_distance_to(end),
+        }
+    }
+
+    /// Circumference of WGS-84 ellipsoid cut by plane going through both points and centre of the Earth.
+    /// None for same or antipodal points, which do not define the plane.
+    fn great_ellipse_circumference(&self, end: &Position) -> Option<f64> {
+        let vector = |pos :&Position| {
+            let (sin_lat, cos_lat) = pos.latitude.to_radians().sin_cos();
+            let (sin_lon, cos_lon) = pos.longitude.to_radians().sin_cos();
+            [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+        };
+        let (p, q) = (vector(self), vector(end));
+        let normal = [p[1] * q[2] - p[2] * q[1], p[2] * q[0] - p[0] * q[2], p[0] * q[1] - p[1] * q[0]];
+        let length = normal.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if length < 1e-9 {
+            return None;
+        }
+
+        // Ellipse reaches highest latitude equal to inclination of the plane, semi-major axis lies in equator.
+        let cos2_incl = (normal[2] / length).powi(2);
+        let b_ellipsoid = (1.0 - WGS84_F) * WGS84_A;
+        let a = WGS84_A;
+        let b = 1.0 / (cos2_incl / (a * a) + (1.0 - cos2_incl) / (b_ellipsoid * b_ellipsoid)).sqrt();
+        // Ramanujan's approximation, error is far below millimeter for shapes this close to circle.
+        let h = ((a - b) / (a + b)).powi(2);
+        Some(std::f64::consts::PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt())))
+    }
+
+    /// Implementation of Vincenty inverse formula on WGS-84 ellipsoid. None when iteration does not converge.
+    pub fn ellipsoid_distance_to(&self, end: &Position) -> Option<Distance> {
+        const A :f64 = WGS84_A;
+        const F :f64 = WGS84_F;
+        const B :f64 = (1.0 - F) * A;
+
+        let l = (end.longitude - self.longitude).to_radians();
+        let u1 = ((1.0 - F) * self.latitude.to_radians().tan()).atan();
+        let u2 = ((1.0 - F) * end.latitude.to_radians().tan()).atan();
+        let (sin_u1, cos_u1) = u1.sin_cos();
+        let (sin_u2, cos_u2) = u2.sin_cos();
+
+        let mut lambda = l;
+        for _ in 0..200 {
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            let sin_sigma = ((cos_u2 * sin_lambda).powi(2) + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2)).sqrt();
+            if sin_sigma == 0.0 {
+                // Same points.
+                return Some(Distance::from_meters(0.0));
+            }
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = sin_sigma.atan2(cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos2_alpha = 1.0 - sin_alpha.powi(2);
+            // Points on equator have no midpoint latitude.
+            let cos_2sigma_m = if cos2_alpha != 0.0 { cos_sigma - 2.0 * sin_u1 * sin_u2 / cos2_alpha } else { 0.0 };
+            let c = F / 16.0 * cos2_alpha * (4.0 + F * (4.0 - 3.0 * cos2_alpha));
+
+            let previous = lambda;
+            lambda = l + (1.0 - c) * F * sin_alpha
+                * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+            if (lambda - previous).abs() > 1e-12 {
+                continue;
+            }
+
+            let u_sq = cos2_alpha * (A * A - B * B) / (B * B);
+            let big_a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b * sin_sigma * (cos_2sigma_m + big_b / 4.0 * (
+                cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                - big_b / 6.0 * cos_2sigma_m * (-3.0 + 4.0 * sin_sigma.powi(2)) * (-3.0 + 4.0 * cos_2sigma_m.powi(2))
+            ));
+            let meters = B * big_a * (sigma - delta_sigma);
+            return Some(Distance::from_meters((meters * 1000.0).round() / 1000.0));
+        }
+        None
     }
 
     pub fn azimuth_to(&self, end: &Position) -> f64 {
@@ -122,6 +280,11 @@ impl Position {
 
         azimuth
     }
+
+    /// Bearing to point the antenna to for the long path.
+    pub fn long_path_azimuth_to(&self, end: &Position) -> f64 {
+        (self.azimuth_to(end) + 180.0) % 360.0
+    }
 }
 
 
@@ -129,6 +292,46 @@ impl Position {
 
 
 
+/// Units distances can be shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DistanceUnit {
+    Meters,
+    #[default]
+    Kilometers,
+    Miles,
+    NauticalMiles,
+}
+
+impl DistanceUnit {
+    pub const ALL :[DistanceUnit; 4] = [DistanceUnit::Kilometers, DistanceUnit::Miles, DistanceUnit::NauticalMiles, DistanceUnit::Meters];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            DistanceUnit::Meters => "m",
+            DistanceUnit::Kilometers => "km",
+            DistanceUnit::Miles => "mi",
+            DistanceUnit::NauticalMiles => "nmi",
+        }
+    }
+
+    pub fn from_symbol(symbol :&str) -> Option<DistanceUnit> {
+        DistanceUnit::ALL.into_iter().find(|unit| unit.symbol() == symbol)
+    }
+
+    fn meters(&self) -> f64 {
+        match self {
+            DistanceUnit::Meters => 1.0,
+            DistanceUnit::Kilometers => 1000.0,
+            // Statute mile.
+            DistanceUnit::Miles => 1609.344,
+            DistanceUnit::NauticalMiles => 1852.0,
+        }
+    }
+}
+
+
+/// Distance stored in meters, converted to any unit when shown.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Distance {
     meters: f64
 }
@@ -140,7 +343,152 @@ impl Distance {
         }
     }
 
+    pub fn meters(&self) -> f64 {
+        self.meters
+    }
+
     pub fn km(&self) -> f64 {
         self.meters / 1000.0
     }
-}
\ No newline at end of file
+
+    pub fn in_unit(&self, unit: DistanceUnit) -> f64 {
+        self.meters / unit.meters()
+    }
+
+    /// Value in given unit with `precision` decimal places, without unit symbol.
+    pub fn format_value(&self, unit: DistanceUnit, precision: usize) -> String {
+        format!("{:.*}", precision, self.in_unit(unit))
+    }
+
+    /// Value with unit symbol, like `12.35 km`.
+    pub fn format(&self, unit: DistanceUnit, precision: usize) -> String {
+        format!("{} {}", self.format_value(unit, precision), unit.symbol())
+    }
+}
+
+// Distances are compared by total order of floats, so they can be sorted and used as keys.
+impl PartialEq for Distance {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Distance {}
+
+impl PartialOrd for Distance {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Distance {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.meters.total_cmp(&other.meters)
+    }
+}
+
+impl std::ops::Add for Distance {
+    type Output = Distance;
+
+    fn add(self, other: Distance) -> Distance {
+        Distance::from_meters(self.meters + other.meters)
+    }
+}
+
+impl std::ops::AddAssign for Distance {
+    fn add_assign(&mut self, other: Distance) {
+        self.meters += other.meters;
+    }
+}
+
+impl std::iter::Sum for Distance {
+    fn sum<I: Iterator<Item = Distance>>(iter: I) -> Distance {
+        iter.fold(Distance::default(), |sum, distance| sum + distance)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_locators_of_all_lengths() {
+        for qth in ["JO", "JO70", "JO70fb", "JO70fb12", "JO70fb12ab"] {
+            let position = Position::from_qth(qth).unwrap();
+            assert_eq!(position.to_qth_precision(qth.len()), qth);
+        }
+        assert_eq!(Position::new(50.0755, 14.4378).to_qth(), "JO70fb");
+        assert_eq!(Position::new(90.0, 180.0).to_qth(), "RR99xx");
+    }
+
+    #[test]
+    fn reports_invalid_locators() {
+        assert_eq!(Position::parse_qth("JO7").unwrap_err(), LocatorError::InvalidLength(3));
+        assert_eq!(Position::parse_qth("JO70fb12ab34").unwrap_err(), LocatorError::InvalidLength(12));
+        assert_eq!(
+            Position::parse_qth("JO70zb").unwrap_err().to_string(),
+            "character 'Z' at position 5 is not valid, expected letter A-X"
+        );
+        assert_eq!(
+            Position::parse_qth("JOA0").unwrap_err(),
+            LocatorError::InvalidCharacter { position: 3, character: 'A', expected: "digit" }
+        );
+    }
+
+    #[test]
+    fn computes_locator_uncertainty() {
+        let six = Position::locator_uncertainty("JO70fb").unwrap().km();
+        let ten = Position::locator_uncertainty("JO70fb12ab").unwrap().km();
+        assert!(six > 3.0 && six < 4.0, "{}", six);
+        assert!(ten < 0.02, "{}", ten);
+    }
+
+    #[test]
+    fn computes_ellipsoid_distance() {
+        // Flinders Peak to Buninyong, example of Vincenty's paper.
+        let flinders = Position::new(-(37.0 + 57.0 / 60.0 + 3.72030 / 3600.0), 144.0 + 25.0 / 60.0 + 29.52440 / 3600.0);
+        let buninyong = Position::new(-(37.0 + 39.0 / 60.0 + 10.15610 / 3600.0), 143.0 + 55.0 / 60.0 + 35.38390 / 3600.0);
+        let meters = flinders.ellipsoid_distance_to(&buninyong).unwrap().meters();
+        assert!((meters - 54972.271).abs() < 0.01, "{}", meters);
+        assert_eq!(flinders.ellipsoid_distance_to(&flinders).unwrap().meters(), 0.0);
+    }
+
+    #[test]
+    fn falls_back_to_sphere_for_antipodal_points() {
+        let start = Position::new(0.0, 0.0);
+        let end = Position::new(0.5, 179.7);
+        assert!(start.ellipsoid_distance_to(&end).is_none());
+        assert_eq!(start.distance_by(&end, DistanceFormula::Wgs84), start.distance_to(&end));
+    }
+
+    #[test]
+    fn computes_long_path() {
+        let prague = Position::new(50.0755, 14.4378);
+        let sydney = Position::new(-33.8688, 151.2093);
+        let circumference = 2.0 * std::f64::consts::PI * EARTH_RADIUS;
+        let sum = prague.distance_to(&sydney).meters() + prague.long_path_distance_to(&sydney).meters();
+        assert!((sum - circumference).abs() < 0.01);
+
+        let short = prague.azimuth_to(&sydney);
+        assert!((prague.long_path_azimuth_to(&sydney) - (short + 180.0) % 360.0).abs() < 1e-9);
+        assert_eq!(DistanceFormula::from_name("wgs84"), Some(DistanceFormula::Wgs84));
+    }
+
+    #[test]
+    fn computes_long_path_on_ellipsoid() {
+        // Meridian and equator of WGS-84 ellipsoid.
+        let north = Position::new(10.0, 15.0);
+        let south = Position::new(-20.0, 15.0);
+        let sum = north.distance_by(&south, DistanceFormula::Wgs84).meters() + north.long_path_distance_by(&south, DistanceFormula::Wgs84).meters();
+        assert!((sum - 40007862.917).abs() < 1.0, "{}", sum);
+
+        let west = Position::new(0.0, 10.0);
+        let east = Position::new(0.0, 50.0);
+        let sum = west.distance_by(&east, DistanceFormula::Wgs84).meters() + west.long_path_distance_by(&east, DistanceFormula::Wgs84).meters();
+        assert!((sum - 2.0 * std::f64::consts::PI * WGS84_A).abs() < 1.0, "{}", sum);
+
+        assert_eq!(west.long_path_distance_by(&east, DistanceFormula::Sphere), west.long_path_distance_to(&east));
+        assert_eq!(west.long_path_distance_by(&west, DistanceFormula::Wgs84), west.long_path_distance_to(&west));
+    }
+}
@@ -0,0 +1,146 @@
+// QSO times are stored as UTC unix timestamps in seconds.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+pub const UTC_FORMAT :&str = "%Y-%m-%d %H:%M:%S";
+
+
+pub fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+pub fn format_utc(time :i64) -> String {
+    NaiveDateTime::from_timestamp_opt(time, 0).map_or("Invalid time".to_string(), |t| t.format(UTC_FORMAT).to_string())
+}
+
+pub fn format_local(time :i64) -> String {
+    Local.timestamp_opt(time, 0).single().map_or("Invalid time".to_string(), |t| t.format(UTC_FORMAT).to_string())
+}
+
+/// Time `input` like "-5m" points to before `now`, None when input is not relative time at all.
+fn parse_relative(input :&str, now :i64) -> Option<Result<i64, String>> {
+    let amount = input.strip_prefix('-')?;
+    let unit = amount.chars().last()?;
+    let unit_seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return None,
+    };
+    let amount = amount[..amount.len() - unit.len_utf8()].trim();
+    if amount.is_empty() || !amount.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let time = amount.parse::<i64>().ok()
+        .and_then(|amount| amount.checked_mul(unit_seconds))
+        .and_then(|seconds| now.checked_sub(seconds))
+        .filter(|time| NaiveDateTime::from_timestamp_opt(*time, 0).is_some());
+    Some(time.ok_or(format!("'{}' is too far in the past", input)))
+}
+
+fn parse_time_of_day(input :&str) -> Option<NaiveTime> {
+    match input.len() {
+        4 if input.chars().all(|c| c.is_ascii_digit()) => NaiveTime::parse_from_str(input, "%H%M").ok(),
+        6 if input.chars().all(|c| c.is_ascii_digit()) => NaiveTime::parse_from_str(input, "%H%M%S").ok(),
+        _ => NaiveTime::parse_from_str(input, "%H:%M:%S")
+            .or(NaiveTime::parse_from_str(input, "%H:%M"))
+            .ok(),
+    }
+}
+
+fn parse_date_time(input :&str) -> Option<NaiveDateTime> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Some(time.naive_utc());
+    }
+
+    let input = input.trim_end_matches(['Z', 'z']).replacen('T', " ", 1);
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%d %H%M", "%Y%m%d %H%M%S", "%Y%m%d %H%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&input, format).ok())
+        .or(NaiveDate::parse_from_str(&input, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+}
+
+/// Parses QSO time typed by operator, all values are in UTC.
+///
+/// Empty input keeps `reference`. Accepts "now", relative time like "-5m" / "-2h" / "-1d",
+/// time of day "HHMM" / "HH:MM" placed into the day of `reference`, and full timestamps like
+/// "2023-09-02 14:05" or RFC 3339. Time of day which would end up in the future is moved
+/// to previous day, so logs typed shortly after midnight keep their date.
+pub fn parse(input :&str, reference :i64, now :i64) -> Result<i64, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(reference);
+    }
+    if input.eq_ignore_ascii_case("now") {
+        return Ok(now);
+    }
+
+    if let Some(time) = parse_relative(input, now) {
+        return time;
+    }
+
+    if let Some(time_of_day) = parse_time_of_day(input) {
+        let day = NaiveDateTime::from_timestamp_opt(reference, 0)
+            .ok_or(format!("Invalid reference time {}", reference))?
+            .date();
+        let time = day.and_time(time_of_day).timestamp();
+        return Ok(if time > now { time - Duration::days(1).num_seconds() } else { time });
+    }
+
+    parse_date_time(input)
+        .map(|t| t.timestamp())
+        .ok_or(format!("'{}' is not valid time, use HHMM, YYYY-MM-DD HH:MM or -5m", input))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-06-01 12:30:00 and 13:53:20 UTC.
+    const REFERENCE :i64 = 1717245000;
+    const NOW :i64 = 1717250000;
+
+    #[test]
+    fn keeps_reference_or_takes_now() {
+        assert_eq!(parse("", REFERENCE, NOW), Ok(REFERENCE));
+        assert_eq!(parse(" NOW ", REFERENCE, NOW), Ok(NOW));
+        assert_eq!(parse("-5m", REFERENCE, NOW), Ok(NOW - 300));
+        assert_eq!(parse("-2h", REFERENCE, NOW), Ok(NOW - 7200));
+        assert_eq!(parse("-1d", REFERENCE, NOW), Ok(NOW - 86400));
+    }
+
+    #[test]
+    fn places_time_of_day_into_reference_day() {
+        assert_eq!(parse("1200", REFERENCE, NOW), Ok(1717243200));
+        assert_eq!(parse("12:00:30", REFERENCE, NOW), Ok(1717243230));
+        assert_eq!(parse("120030", REFERENCE, NOW), Ok(1717243230));
+        // Would be in the future, so it is the day before.
+        assert_eq!(parse("23:50", REFERENCE, NOW), Ok(1717199400));
+    }
+
+    #[test]
+    fn parses_full_timestamps() {
+        assert_eq!(parse("2023-09-02 14:05", REFERENCE, NOW), Ok(1693663500));
+        assert_eq!(parse("2023-09-02T14:05:00Z", REFERENCE, NOW), Ok(1693663500));
+        assert_eq!(parse("2023-09-02T16:05:00+02:00", REFERENCE, NOW), Ok(1693663500));
+        assert_eq!(parse("20230902 1405", REFERENCE, NOW), Ok(1693663500));
+        assert_eq!(parse("2023-09-02", REFERENCE, NOW), Ok(1693612800));
+    }
+
+    #[test]
+    fn rejects_invalid_time() {
+        assert!(parse("2561", REFERENCE, NOW).is_err());
+        assert!(parse("-5x", REFERENCE, NOW).is_err());
+        assert!(parse("--5m", REFERENCE, NOW).is_err());
+        assert_eq!(parse("-999999999999d", REFERENCE, NOW), Err("'-999999999999d' is too far in the past".to_string()));
+        assert!(parse("-99999999999999999999s", REFERENCE, NOW).is_err());
+        assert_eq!(
+            parse("yesterday", REFERENCE, NOW),
+            Err("'yesterday' is not valid time, use HHMM, YYYY-MM-DD HH:MM or -5m".to_string())
+        );
+        assert_eq!(format_utc(REFERENCE), "2024-06-01 12:30:00");
+    }
+}
@@ -2,7 +2,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use ratatui::widgets::ListItem;
 use crate::database::{macros::{declare_table, define_table_data}, SchemaStep, DBObjectSerializable, DBSchemaObject};
-use super::data_store::DataStoreTrait;
+use super::data_store::{DataStoreTrait, Describe};
 
 
 declare_table!(Race,
@@ -14,6 +14,15 @@ declare_table!(Race,
             my_location TEXT   ,
             my_call     TEXT
         )"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE Race ADD COLUMN station_id INTEGER"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE Race ADD COLUMN scoring TEXT NOT NULL DEFAULT ''"
+    ),
+    SchemaStep::SQL(
+        "ALTER TABLE Race ADD COLUMN dupe_rule TEXT NOT NULL DEFAULT ''"
     )
 );
 
@@ -21,7 +30,10 @@ define_table_data!(Race,
     (create_time: u32    ),
     (name       : String ),
     (my_location: String ),
-    (my_call    : String )
+    (my_call    : String ),
+    (station_id : Option<i64>),
+    (scoring    : String ),
+    (dupe_rule  : String )
 );
 
 impl Default for Race {
@@ -31,7 +43,10 @@ impl Default for Race {
             create_time: SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backward").as_secs() as u32,
             name: "".to_string(),
             my_location: "".to_string(),
-            my_call: "".to_string()
+            my_call: "".to_string(),
+            station_id: None,
+            scoring: "".to_string(),
+            dupe_rule: "".to_string(),
         }
     }
 }
@@ -41,3 +56,9 @@ impl<'a> Into<ListItem<'a>> for Race {
         ListItem::new(self.name)
     }
 }
+
+impl Describe for Race {
+    fn describe(&self) -> String {
+        format!("race {}", self.name)
+    }
+}
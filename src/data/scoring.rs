@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use crate::app_errors::AppError;
+use super::{LogEntry, dxcc};
+
+
+/// Rule sets race can use, custom rules are written as `key=value` pairs.
+pub const SCORING_HELP :&str = "qrb, square, dxcc or custom like 'qso=2 km=1 mult=square'";
+pub const DEFAULT_SCORING :&str = "qrb";
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Multiplier {
+    None,
+    // Every worked 4 character locator square.
+    Square,
+    // Every worked country.
+    Dxcc,
+}
+
+
+/// Points of QSO are `qso + km * distance`, total of the race is sum of points multiplied by number of multipliers.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScoringRules {
+    pub qso_points: f64,
+    pub km_points: f64,
+    pub multiplier: Multiplier,
+}
+
+impl ScoringRules {
+    fn preset(name :&str) -> Option<ScoringRules> {
+        Some(match name {
+            // VHF contests, 1 point for every km of QRB.
+            "qrb" => ScoringRules { qso_points: 0.0, km_points: 1.0, multiplier: Multiplier::None },
+            "square" => ScoringRules { qso_points: 1.0, km_points: 0.0, multiplier: Multiplier::Square },
+            "dxcc" => ScoringRules { qso_points: 1.0, km_points: 0.0, multiplier: Multiplier::Dxcc },
+            _ => return None,
+        })
+    }
+
+    /// Parses preset name optionally followed by overrides, e.g. `qrb mult=square` or `qso=2 km=1`.
+    pub fn parse(definition :&str) -> Result<ScoringRules, AppError> {
+        let invalid = |part :&str| AppError::InvalidScoring(format!("'{}', use {}", part, SCORING_HELP));
+        let definition = definition.trim().to_lowercase();
+        let mut rules = ScoringRules { qso_points: 1.0, km_points: 0.0, multiplier: Multiplier::None };
+        if definition.is_empty() {
+            return Ok(ScoringRules::preset(DEFAULT_SCORING).unwrap_or(rules));
+        }
+
+        for part in definition.split_whitespace() {
+            if let Some(preset) = ScoringRules::preset(part) {
+                rules = preset;
+                continue;
+            }
+
+            let (key, value) = part.split_once('=').ok_or_else(|| invalid(part))?;
+            match key {
+                "qso" => rules.qso_points = value.parse().map_err(|_| invalid(part))?,
+                "km" => rules.km_points = value.parse().map_err(|_| invalid(part))?,
+                "mult" => rules.multiplier = match value {
+                    "none" => Multiplier::None,
+                    "square" => Multiplier::Square,
+                    "dxcc" => Multiplier::Dxcc,
+                    _ => return Err(invalid(part)),
+                },
+                _ => return Err(invalid(part)),
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Rules falling back to default ones when race definition is not valid.
+    pub fn parse_or_default(definition :&str) -> ScoringRules {
+        ScoringRules::parse(definition)
+            .or(ScoringRules::parse(DEFAULT_SCORING))
+            .expect("Default scoring has to be valid")
+    }
+
+    /// Distance is counted in whole kilometers as usual in contests.
+    pub fn qso_points(&self, distance_km :Option<f64>) -> f64 {
+        self.qso_points + self.km_points * distance_km.unwrap_or(0.0).round()
+    }
+
+    pub fn multiplier_of(&self, log :&LogEntry) -> Option<String> {
+        let key = match self.multiplier {
+            Multiplier::None => return None,
+            Multiplier::Square => log.locator.chars().take(4).collect::<String>().to_uppercase(),
+            Multiplier::Dxcc => dxcc::entity_key(&log.call),
+        };
+        (!key.is_empty()).then_some(key)
+    }
+}
+
+
+/// Score of logs counted under given rules, duplicate QSOs score nothing.
+#[derive(Clone, Default, Debug)]
+pub struct Score {
+    pub qsos: usize,
+    pub points: f64,
+    multipliers: HashSet<String>,
+    multiplied: bool,
+}
+
+impl Score {
+    pub fn new(rules :&ScoringRules) -> Score {
+        Score {
+            multiplied: rules.multiplier != Multiplier::None,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, rules :&ScoringRules, log :&LogEntry, distance_km :Option<f64>) {
+        if log.dupe {
+            return;
+        }
+        self.qsos += 1;
+        self.points += rules.qso_points(distance_km);
+        if let Some(multiplier) = rules.multiplier_of(log) {
+            self.multipliers.insert(multiplier);
+        }
+    }
+
+    pub fn multipliers(&self) -> usize {
+        self.multipliers.len()
+    }
+
+    pub fn total(&self) -> f64 {
+        match self.multiplied {
+            true => self.points * self.multipliers.len() as f64,
+            false => self.points,
+        }
+    }
+
+    /// How much total score would grow with given QSO, and if it would be a new multiplier.
+    pub fn delta(&self, rules :&ScoringRules, log :&LogEntry, distance_km :Option<f64>) -> (f64, bool) {
+        let mut with_log = self.clone();
+        with_log.add(rules, log, distance_km);
+        (with_log.total() - self.total(), with_log.multipliers() > self.multipliers())
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use rusqlite::Connection;
+
+use crate::app_errors::AppError;
+
+
+/// Log found by full text search.
+pub struct SearchHit {
+    pub log_id: i64,
+    // Found only by similar words, query itself does not match.
+    pub fuzzy: bool,
+}
+
+
+/// Words of the query as full text index splits them, lowercase.
+fn query_terms(query :&str) -> Vec<String> {
+    query.split(|c :char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+        .collect()
+}
+
+fn quote(term :&str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+fn levenshtein(a :&str, b :&str) -> usize {
+    let b :Vec<char> = b.chars().collect();
+    let mut previous :Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Number of typos tolerated in term of given length, short terms have to match exactly.
+fn allowed_distance(length :usize) -> usize {
+    match length {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+fn run_query(conn :&Connection, fts_query :&str, limit :usize) -> Result<Vec<i64>, AppError> {
+    let mut stmt = conn.prepare("SELECT rowid FROM LogEntrySearch WHERE LogEntrySearch MATCH ?1 ORDER BY rank LIMIT ?2")?;
+    let rows = stmt.query_map((fts_query, limit as i64), |row| row.get(0))?;
+    Ok(rows.collect::<Result<Vec<i64>, rusqlite::Error>>()?)
+}
+
+/// Indexed words similar to the term.
+fn similar_terms(conn :&Connection, term :&str) -> Result<Vec<String>, AppError> {
+    let length = term.chars().count();
+    let distance = allowed_distance(length);
+    if distance == 0 {
+        return Ok(vec!());
+    }
+
+    let mut stmt = conn.prepare("SELECT term FROM LogEntrySearchVocab WHERE length(term) BETWEEN ?1 AND ?2")?;
+    let rows = stmt.query_map(((length - distance) as i64, (length + distance) as i64), |row| row.get::<_, String>(0))?;
+
+    let mut similar = Vec::new();
+    for candidate in rows {
+        let candidate = candidate?;
+        if candidate != term && levenshtein(term, &candidate) <= distance {
+            similar.push(candidate);
+        }
+    }
+    Ok(similar)
+}
+
+/// Searches calls, locators, codes and notes of all logs. Every word of the query has to match
+/// beginning of some indexed word, best ranked hits are first. When there are not enough of them,
+/// logs with words differing by a typo are added after them.
+pub fn search(conn :&Connection, query :&str, limit :usize) -> Result<Vec<SearchHit>, AppError> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Ok(vec!());
+    }
+
+    let prefix_query = terms.iter()
+        .map(|term| format!("{}*", quote(term)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let mut hits :Vec<SearchHit> = run_query(conn, &prefix_query, limit)?.into_iter()
+        .map(|log_id| SearchHit { log_id, fuzzy: false })
+        .collect();
+    if hits.len() >= limit {
+        return Ok(hits);
+    }
+
+    let mut has_similar = false;
+    let mut fuzzy_parts = Vec::new();
+    for term in &terms {
+        let mut alternatives = vec!(format!("{}*", quote(term)));
+        for similar in similar_terms(conn, term)? {
+            has_similar = true;
+            alternatives.push(quote(&similar));
+        }
+        fuzzy_parts.push(format!("({})", alternatives.join(" OR ")));
+    }
+    if !has_similar {
+        return Ok(hits);
+    }
+
+    let found :HashSet<i64> = hits.iter().map(|hit| hit.log_id).collect();
+    for log_id in run_query(conn, &fuzzy_parts.join(" AND "), limit)? {
+        if hits.len() >= limit {
+            break;
+        }
+        if !found.contains(&log_id) {
+            hits.push(SearchHit { log_id, fuzzy: true });
+        }
+    }
+    Ok(hits)
+}
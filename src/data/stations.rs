@@ -0,0 +1,54 @@
+use ratatui::widgets::ListItem;
+use crate::database::{macros::{declare_table, define_table_data}, SchemaStep, DBObjectSerializable, DBSchemaObject};
+use super::data_store::{DataStoreTrait, Describe};
+
+
+declare_table!(StationProfile,
+    SchemaStep::SQL(
+        "CREATE TABLE StationProfile (
+            id          INTEGER PRIMARY KEY,
+            call        TEXT   ,
+            locator     TEXT   ,
+            antenna     TEXT   ,
+            power       TEXT   ,
+            description TEXT   ,
+            active      BOOLEAN
+        )"
+    )
+);
+
+define_table_data!(StationProfile,
+    (call       : String),
+    (locator    : String),
+    (antenna    : String),
+    (power      : String),
+    (description: String),
+    (active     : bool  )
+);
+
+impl Default for StationProfile {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            call: "".to_string(),
+            locator: "".to_string(),
+            antenna: "".to_string(),
+            power: "".to_string(),
+            description: "".to_string(),
+            active: false,
+        }
+    }
+}
+
+impl<'a> From<StationProfile> for ListItem<'a> {
+    fn from(station :StationProfile) -> Self {
+        let marker = if station.active { "* " } else { "  " };
+        ListItem::new(format!("{}#{} {} {} {}", marker, station.id, station.call, station.locator, station.description))
+    }
+}
+
+impl Describe for StationProfile {
+    fn describe(&self) -> String {
+        format!("station {}", self.call)
+    }
+}
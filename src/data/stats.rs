@@ -0,0 +1,73 @@
+use std::collections::{HashSet, BTreeMap};
+
+use super::{LogEntry, DistanceCache, position::Distance};
+
+
+/// Compass sector names of azimuth distribution, each sector is 45 degrees wide centered on its direction.
+pub const AZIMUTH_SECTORS :[&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+
+/// Summary of logs shown in statistics screen.
+#[derive(Default)]
+pub struct LogStats {
+    pub qso_count: usize,
+    pub unique_calls: usize,
+    pub unique_squares: usize,
+    // Longest distance with id of the log it was reached with.
+    pub odx: Option<(i64, Distance)>,
+    pub average_distance: Option<Distance>,
+    // Number of QSOs in every hour with any, keyed by unix time of the hour start.
+    pub per_hour: BTreeMap<i64, u64>,
+    pub azimuth_sectors: [u64; 8],
+}
+
+impl LogStats {
+    pub fn compute<'a>(logs :impl Iterator<Item = &'a LogEntry>, distances :&DistanceCache) -> LogStats {
+        let mut stats = LogStats::default();
+        let mut calls = HashSet::new();
+        let mut squares = HashSet::new();
+        let mut distance_sum = Distance::default();
+        let mut distance_count = 0;
+
+        for log in logs {
+            stats.qso_count += 1;
+            calls.insert(log.call.to_uppercase());
+            let square :String = log.locator.chars().take(4).collect();
+            if square.len() == 4 {
+                squares.insert(square.to_uppercase());
+            }
+            *stats.per_hour.entry(log.time - log.time.rem_euclid(3600)).or_default() += 1;
+
+            if let Some(distance) = distances.distance(log.id) {
+                distance_sum += distance;
+                distance_count += 1;
+                if stats.odx.is_none_or(|(_, odx)| distance > odx) {
+                    stats.odx = Some((log.id, distance));
+                }
+            }
+            if let Some(azimuth) = distances.azimuth(log.id) {
+                let sector = ((azimuth + 22.5).rem_euclid(360.0) / 45.0) as usize;
+                stats.azimuth_sectors[sector.min(7)] += 1;
+            }
+        }
+
+        stats.unique_calls = calls.len();
+        stats.unique_squares = squares.len();
+        stats.average_distance = (distance_count > 0).then(|| Distance::from_meters(distance_sum.meters() / distance_count as f64));
+        stats
+    }
+
+    /// QSO counts of consecutive hours ending with the last hour any QSO was made in, at most `count` of them.
+    pub fn last_hours(&self, count :usize) -> Vec<(i64, u64)> {
+        let Some(last) = self.per_hour.keys().next_back().copied() else {
+            return vec!();
+        };
+        let first = self.per_hour.keys().next().copied().unwrap_or(last);
+        let hours = (((last - first) / 3600) as usize + 1).min(count);
+
+        (0..hours).rev()
+            .map(|back| last - back as i64 * 3600)
+            .map(|hour| (hour, self.per_hour.get(&hour).copied().unwrap_or(0)))
+            .collect()
+    }
+}
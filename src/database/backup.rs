@@ -0,0 +1,142 @@
+use std::{fs::create_dir_all, path::{Path, PathBuf}, time::Duration};
+
+use rusqlite::{backup::Backup, Connection, OpenFlags};
+
+use crate::app_errors::AppError;
+
+
+/// Number of backups kept for each logbook, oldest ones are removed first.
+pub const BACKUP_COUNT :usize = 10;
+
+/// Reason of backups made before schema migration. They are never removed by rotation, as they are
+/// the only way back to the version of the application logbook was upgraded from.
+pub const PREMIGRATION_REASON :&str = "premigration";
+
+const BACKUP_EXTENSION :&str = "sqlite";
+
+
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created: i64,
+    pub qso_count: Option<i64>,
+}
+
+
+/// Backups are kept in directory next to the logbook so they move together with it.
+pub fn backups_dir(db_path :&Path) -> PathBuf {
+    let stem = db_path.file_stem().map_or("data".to_string(), |s| s.to_string_lossy().to_string());
+    db_path.with_file_name(format!("{}.backups", stem))
+}
+
+/// Copies live database into new timestamped file using SQLite online backup API.
+/// Backup `keep` is never removed by rotation of old backups, even when it is the oldest one.
+pub fn create(conn :&Connection, db_path :&Path, reason :&str, keep :Option<&Path>) -> Result<PathBuf, AppError> {
+    let dir = backups_dir(db_path);
+    create_dir_all(&dir)?;
+
+    let stem = db_path.file_stem().map_or("data".to_string(), |s| s.to_string_lossy().to_string());
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let target_path = dir.join(format!("{}_{}_{}.{}", stem, timestamp, reason, BACKUP_EXTENSION));
+
+    copy(conn, &target_path)?;
+    rotate(&dir, keep)?;
+    Ok(target_path)
+}
+
+fn copy(source :&Connection, target_path :&Path) -> Result<(), AppError> {
+    let mut target = Connection::open(target_path)?;
+    let backup = Backup::new(source, &mut target)?;
+    backup.run_to_completion(256, Duration::from_millis(5), None)?;
+    Ok(())
+}
+
+/// Replaces content of live database by content of backup file.
+pub fn restore(conn :&mut Connection, backup_path :&Path) -> Result<(), AppError> {
+    let source = Connection::open_with_flags(backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let backup = Backup::new(&source, conn)?;
+    backup.run_to_completion(256, Duration::from_millis(5), None)?;
+    Ok(())
+}
+
+fn backup_files(dir :&Path) -> Result<Vec<PathBuf>, AppError> {
+    if !dir.exists() {
+        return Ok(vec!());
+    }
+
+    let mut files :Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION))
+        .collect();
+
+    // File names start with timestamp so newest sorts last.
+    files.sort();
+    Ok(files)
+}
+
+fn is_premigration(path :&Path) -> bool {
+    path.file_stem().is_some_and(|stem| stem.to_string_lossy().ends_with(&format!("_{}", PREMIGRATION_REASON)))
+}
+
+fn rotate(dir :&Path, keep :Option<&Path>) -> Result<(), AppError> {
+    let files :Vec<PathBuf> = backup_files(dir)?.into_iter().filter(|path| !is_premigration(path)).collect();
+    let removable :Vec<&PathBuf> = files.iter().filter(|path| Some(path.as_path()) != keep).collect();
+    let excess = files.len().saturating_sub(BACKUP_COUNT).min(removable.len());
+    for path in &removable[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn count_qsos(path :&Path) -> Option<i64> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    conn.query_row("SELECT COUNT(*) FROM LogEntry", [], |row| row.get(0)).ok()
+}
+
+/// Backups of given logbook, newest first.
+pub fn list(db_path :&Path) -> Result<Vec<BackupInfo>, AppError> {
+    let mut backups :Vec<BackupInfo> = backup_files(&backups_dir(db_path))?
+        .into_iter()
+        .map(|path| {
+            let created = std::fs::metadata(&path).and_then(|m| m.modified()).ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64);
+            BackupInfo {
+                qso_count: count_qsos(&path),
+                path,
+                created,
+            }
+        })
+        .collect();
+    backups.reverse();
+    Ok(backups)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_keeps_premigration_and_kept_backups() {
+        let dir = std::env::temp_dir().join(format!("backup_rotation_{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let file = |name :String| {
+            let path = dir.join(name);
+            std::fs::write(&path, b"").unwrap();
+            path
+        };
+        let premigration = file(format!("data_20230101_000000_{}.{}", PREMIGRATION_REASON, BACKUP_EXTENSION));
+        let kept = file(format!("data_20230101_000001_startup.{}", BACKUP_EXTENSION));
+        for index in 0..12 {
+            file(format!("data_20240101_0000{:02}_startup.{}", index, BACKUP_EXTENSION));
+        }
+
+        rotate(&dir, Some(&kept)).unwrap();
+        let files = backup_files(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files.len(), BACKUP_COUNT + 1);
+        assert!(files.contains(&premigration));
+        assert!(files.contains(&kept));
+    }
+}
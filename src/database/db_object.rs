@@ -17,6 +17,8 @@ pub trait DBObjectSerializable where Self :Sized {
     fn from_row(row :&rusqlite::Row) -> Result<Self, rusqlite::Error>;
 
     fn insert_row(&mut self, conn :&Connection) -> Result<(), rusqlite::Error>;
+    /// Inserts previously deleted row back with its original id.
+    fn restore_row(&self, conn :&Connection) -> Result<(), rusqlite::Error>;
     fn update_row(&self, conn :&Connection) -> Result<(), rusqlite::Error>;
     fn delete_row(&self, conn :&Connection) -> Result<(), rusqlite::Error>;
 }
\ No newline at end of file
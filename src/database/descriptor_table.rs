@@ -48,6 +48,10 @@ impl DBObjectSerializable for TableDescriptor {
     }
 
     fn insert_row(&mut self, conn :&Connection) -> Result<(), rusqlite::Error> {
+        self.restore_row(conn)
+    }
+
+    fn restore_row(&self, conn :&Connection) -> Result<(), rusqlite::Error> {
         conn.execute("INSERT INTO TableDescriptor (name, schema_version) VALUES (?1, ?2)", (&self.name, &self.schema_version))?;
         Ok(())
     }
@@ -29,7 +29,7 @@ macro_rules! define_table_data {
     ($struct_name:ident, $( ($field:ident: $field_type:path) ),+) => {
 
         // Define DB data structure
-        #[derive(Debug, Clone, PartialEq)]
+        #[derive(Debug, Clone, PartialEq, serde::Serialize)]
         pub struct $struct_name {
             pub id: i64,
             $(
@@ -58,13 +58,39 @@ macro_rules! define_table_data {
                     sql.push_str(")");
                     sql
                 };
+                // Tuples implement Params only up to 16 values, list of values has no such limit.
                 conn.execute(&sql,
-                    ( $(&self.$field,)* )
+                    rusqlite::params![ $(&self.$field,)* ]
                 )?;
                 self.id = conn.last_insert_rowid();
                 Ok(())
             }
 
+            fn restore_row(&self, conn :&rusqlite::Connection) -> Result<(), rusqlite::Error> {
+                let sql = {
+                    let mut sql = format!("INSERT INTO {} (id,", stringify!($struct_name));
+                    $(
+                        sql.push_str(stringify!($field));
+                        sql.push_str(",");
+                    )*
+                    sql.pop();
+                    sql.push_str(") VALUES (?1,");
+                    let mut val_index = 1;
+                    $(
+                        val_index += 1;
+                        let _ = &self.$field;
+                        sql.push_str(&format!("?{},", val_index));
+                    )*
+                    sql.pop();
+                    sql.push_str(")");
+                    sql
+                };
+                conn.execute(&sql,
+                    rusqlite::params![ &self.id, $(&self.$field,)* ]
+                )?;
+                Ok(())
+            }
+
             fn update_row(&self, conn :&rusqlite::Connection) -> Result<(), rusqlite::Error> {
                 let sql = {
                     let mut sql = format!("UPDATE {} SET ", stringify!($struct_name));
@@ -78,7 +104,7 @@ macro_rules! define_table_data {
                     sql
                 };
                 conn.execute(&sql,
-                    ( $(&self.$field),* , &self.id )
+                    rusqlite::params![ $(&self.$field),* , &self.id ]
                 )?;
                 Ok(())
             }
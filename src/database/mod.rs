@@ -1,7 +1,7 @@
-use std::fs::create_dir_all;
+use std::{fs::create_dir_all, path::{Path, PathBuf}};
 
 use platform_dirs::AppDirs;
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
 
 mod db_object;
 pub use db_object::{DBSchemaObject, DBObjectSerializable, SchemaStep};
@@ -13,55 +13,155 @@ use crate::app_errors::AppError;
 
 pub mod macros;
 
+mod recent;
+pub use recent::RecentLogbooks;
+
+pub mod backup;
+use backup::BackupInfo;
+
 
 pub struct Database {
     connection: Connection,
+    path: PathBuf,
+    read_only: bool,
+    change_counter: u64,
+    // Backup before the first migration of this open was made already.
+    migration_backed_up: bool,
 }
 
 impl Database {
-    pub fn from_app_database() -> Result<Database, AppError> {
-        let app_paths = AppDirs::new(
+    pub fn app_dirs() -> Result<AppDirs, AppError> {
+        Ok(AppDirs::new(
             Some("org.sadovsf.radio_log"), false
         ).ok_or(
             std::io::Error::new(std::io::ErrorKind::NotFound, "Unable to find user directory!")
-        )?;
-
-        let mut db_path = app_paths.data_dir.clone();
-        create_dir_all(&db_path)?;
+        )?)
+    }
 
+    pub fn default_path() -> Result<PathBuf, AppError> {
+        let mut db_path = Database::app_dirs()?.data_dir;
         db_path.push("data.sqlite");
-        let full_path = db_path.to_str().expect("Failed to convert path to string");
+        Ok(db_path)
+    }
 
-        Ok(Database::new(full_path)?)
+    /// Opens logbook stored in given file, new file is created when it does not exist yet.
+    pub fn from_path(path :&Path) -> Result<Database, AppError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            create_dir_all(parent)?;
+        }
+        Database::new(path)
     }
 
-    pub fn new(path :&str) -> Result<Self, rusqlite::Error> {
+    /// Existing logbook is backed up right after opening, and once more before its schema is migrated.
+    pub fn new(path :&Path) -> Result<Self, AppError> {
+        let is_new = std::fs::metadata(path).map_or(true, |m| m.len() == 0);
         let mut inst = Self {
-            connection: Connection::open(path).expect("Failed to open database"),
+            connection: Connection::open(path)?,
+            path: path.canonicalize().unwrap_or(path.to_path_buf()),
+            read_only: false,
+            change_counter: 0,
+            migration_backed_up: false,
         };
+        if !is_new {
+            inst.backup("startup")?;
+        }
         inst.register_type::<TableDescriptor>()?;
         Ok(inst)
     }
 
-    pub fn register_type<T :DBSchemaObject + DBObjectSerializable>(&mut self) -> Result<(), rusqlite::Error> {
-        let stmt = self.connection.prepare("SELECT * FROM TableDescriptor WHERE name=?1");
-        if stmt.is_ok() {
-            let descriptor = stmt.unwrap().query_row([T::table_name()], |row| -> Result<TableDescriptor, rusqlite::Error> {
-                let name :String = row.get(0)?;
-                assert_eq!(name, T::table_name());
+    /// Opens existing logbook without any schema migration, all changes are refused.
+    /// Allows to look into logbooks written by newer version of the application.
+    pub fn open_read_only(path :&Path) -> Result<Self, AppError> {
+        let mut inst = Self {
+            connection: Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?,
+            path: path.canonicalize().unwrap_or(path.to_path_buf()),
+            read_only: true,
+            change_counter: 0,
+            migration_backed_up: false,
+        };
+        inst.register_type::<TableDescriptor>()?;
+        Ok(inst)
+    }
 
-                TableDescriptor::from_row(row)
-            });
+    /// Sequence number of new change made by user. Shared by all data stores so undo can follow
+    /// the order of changes across tables.
+    pub fn next_change(&mut self) -> u64 {
+        self.change_counter += 1;
+        self.change_counter
+    }
 
-            self.update_schema::<T>(descriptor.ok())?;
-        } else {
-            drop(stmt);
-            self.update_schema::<T>(None)?;
+    /// Sequence number of the latest change made by user, undo and redo do not change it.
+    pub fn last_change(&self) -> u64 {
+        self.change_counter
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self) -> Result<(), AppError> {
+        match self.read_only {
+            true => Err(AppError::ReadOnly),
+            false => Ok(()),
+        }
+    }
+
+    pub fn backup(&self, reason :&str) -> Result<PathBuf, AppError> {
+        backup::create(&self.connection, &self.path, reason, None)
+    }
+
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>, AppError> {
+        backup::list(&self.path)
+    }
+
+    /// Replaces logbook content by the backup. Current state is backed up first so restore can be undone,
+    /// restored backup is kept even when it is the oldest one.
+    pub fn restore_backup(&mut self, backup_path :&Path) -> Result<(), AppError> {
+        self.check_writable()?;
+        backup::create(&self.connection, &self.path, "pre_restore", Some(backup_path))?;
+        backup::restore(&mut self.connection, backup_path)?;
+        self.register_type::<TableDescriptor>()
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn stored_descriptor<T :DBSchemaObject>(&self) -> Option<TableDescriptor> {
+        let mut stmt = self.connection.prepare("SELECT * FROM TableDescriptor WHERE name=?1").ok()?;
+        stmt.query_row([T::table_name()], |row| -> Result<TableDescriptor, rusqlite::Error> {
+            let name :String = row.get(0)?;
+            assert_eq!(name, T::table_name());
+
+            TableDescriptor::from_row(row)
+        }).ok()
+    }
+
+    /// Fails when table was written by newer version of the application. Called for all tables
+    /// before any of them is registered, so logbook is never left migrated only partially.
+    pub fn check_schema<T :DBSchemaObject>(&self) -> Result<(), AppError> {
+        let last_known_version = self.stored_descriptor::<T>().map_or(0, |descriptor| descriptor.schema_version);
+        let supported = T::schema().len();
+
+        // Newer tables are read as they are, which works only while later versions did not drop
+        // or change columns read by this one, writing into them is never safe.
+        if last_known_version as usize > supported && !self.read_only {
+            return Err(AppError::SchemaTooNew {
+                table: T::table_name().to_string(),
+                found: last_known_version,
+                supported,
+            });
         }
         Ok(())
     }
 
-    fn update_schema<T :DBSchemaObject + DBObjectSerializable>(&mut self, descriptor :Option<TableDescriptor>) -> Result<(), rusqlite::Error> {
+    pub fn register_type<T :DBSchemaObject + DBObjectSerializable>(&mut self) -> Result<(), AppError> {
+        let descriptor = self.stored_descriptor::<T>();
+        self.update_schema::<T>(descriptor)
+    }
+
+    fn update_schema<T :DBSchemaObject + DBObjectSerializable>(&mut self, descriptor :Option<TableDescriptor>) -> Result<(), AppError> {
+        self.check_schema::<T>()?;
         let schema = T::schema();
 
         let last_known_version = match descriptor.as_ref() {
@@ -69,12 +169,16 @@ impl Database {
             None => 0,
         };
 
-        if last_known_version as usize > schema.len() {
-            panic!("Database schema is newer than the application");
-        }
-        if last_known_version as usize == schema.len() {
+        if last_known_version as usize >= schema.len() {
             return Ok(());
         }
+        self.check_writable()?;
+
+        // Only tables which already exist hold data worth saving, one backup covers all of them.
+        if descriptor.is_some() && !self.migration_backed_up {
+            self.backup(backup::PREMIGRATION_REASON)?;
+            self.migration_backed_up = true;
+        }
 
         let ts = self.connection.transaction()?;
         for step in &schema[last_known_version as usize..] {
@@ -126,16 +230,51 @@ impl Database {
         Ok(result)
     }
 
-    pub fn insert(&mut self, obj :&mut impl DBObjectSerializable) -> Result<(), rusqlite::Error> {
-        obj.insert_row(&mut self.connection)
+    pub fn insert(&mut self, obj :&mut impl DBObjectSerializable) -> Result<(), AppError> {
+        self.check_writable()?;
+        Ok(obj.insert_row(&self.connection)?)
+    }
+
+    /// Inserts all objects in single transaction so either all or none of them are stored.
+    /// Savepoint is used so it can be part of outer transaction too.
+    pub fn insert_all<T :DBObjectSerializable>(&mut self, objs :&mut [T]) -> Result<(), AppError> {
+        self.check_writable()?;
+        let sp = self.connection.savepoint()?;
+        for obj in objs.iter_mut() {
+            obj.insert_row(&sp)?;
+        }
+        Ok(sp.commit()?)
+    }
+
+    /// Runs all writes done by `f` in single transaction, nothing is stored when any of them fails.
+    pub fn in_transaction<R>(&mut self, f :impl FnOnce(&mut Database) -> Result<R, AppError>) -> Result<R, AppError> {
+        self.check_writable()?;
+        self.connection.execute_batch("BEGIN")?;
+        match f(self) {
+            Ok(result) => {
+                self.connection.execute_batch("COMMIT")?;
+                Ok(result)
+            },
+            Err(err) => {
+                let _ = self.connection.execute_batch("ROLLBACK");
+                Err(err)
+            }
+        }
+    }
+
+    pub fn restore(&mut self, obj :&impl DBObjectSerializable) -> Result<(), AppError> {
+        self.check_writable()?;
+        Ok(obj.restore_row(&self.connection)?)
     }
 
-    pub fn update(&mut self, obj :&impl DBObjectSerializable) -> Result<(), rusqlite::Error> {
-        obj.update_row(&mut self.connection)
+    pub fn update(&mut self, obj :&impl DBObjectSerializable) -> Result<(), AppError> {
+        self.check_writable()?;
+        Ok(obj.update_row(&self.connection)?)
     }
 
-    pub fn delete(&mut self, obj :&impl DBObjectSerializable) -> Result<(), rusqlite::Error> {
-        obj.delete_row(&mut self.connection)
+    pub fn delete(&mut self, obj :&impl DBObjectSerializable) -> Result<(), AppError> {
+        self.check_writable()?;
+        Ok(obj.delete_row(&self.connection)?)
     }
 
     pub fn get_connection(&self) -> &Connection {
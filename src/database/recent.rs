@@ -0,0 +1,62 @@
+use std::{fs::create_dir_all, path::{Path, PathBuf}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_errors::AppError;
+
+use super::Database;
+
+
+const MAX_RECENT :usize = 10;
+
+
+/// List of recently opened logbook files, most recent first. Stored as JSON in the app config dir.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct RecentLogbooks {
+    pub paths: Vec<PathBuf>,
+}
+
+impl RecentLogbooks {
+    fn file_path() -> Result<PathBuf, AppError> {
+        let mut path = Database::app_dirs()?.config_dir;
+        path.push("recent_logbooks.json");
+        Ok(path)
+    }
+
+    /// Missing or broken file results in empty list, it is just convenience for the user.
+    pub fn load() -> Self {
+        RecentLogbooks::file_path().ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let path = RecentLogbooks::file_path()?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn push(&mut self, path :&Path) {
+        let path = path.canonicalize().unwrap_or(path.to_path_buf());
+        self.paths.retain(|p| *p != path);
+        self.paths.insert(0, path);
+        self.paths.truncate(MAX_RECENT);
+    }
+
+    /// Adds logbook to the top of stored list.
+    pub fn remember(path :&Path) -> Result<(), AppError> {
+        let mut recent = RecentLogbooks::load();
+        recent.push(path);
+        recent.save()
+    }
+
+    pub fn remove(&mut self, path :&Path) {
+        self.paths.retain(|p| p != path);
+    }
+}
@@ -0,0 +1,497 @@
+use std::io::Write;
+
+use chrono::NaiveDateTime;
+
+use crate::data::{LogEntry, Race, ConfigData, StationProfile, band};
+
+
+pub const ADIF_VERSION :&str = "3.1.4";
+const PROGRAM_ID :&str = env!("CARGO_PKG_NAME");
+const PROGRAM_VERSION :&str = env!("CARGO_PKG_VERSION");
+
+
+/// Single ADIF record as ordered list of (field name, value) pairs.
+pub type AdifRecord = Vec<(String, String)>;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdifFormat {
+    /// Tag based text format (.adi)
+    Adi,
+    /// XML based format (.adx)
+    Adx,
+}
+
+
+fn push_field(record :&mut AdifRecord, name :&str, value :&str) {
+    if value.is_empty() {
+        return;
+    }
+    record.push((name.to_string(), value.to_string()));
+}
+
+/// Station details are taken from station profile of the log, then its race and finally from configuration.
+pub fn log_to_record(log :&LogEntry, station :Option<&StationProfile>, race :Option<&Race>, config :&ConfigData) -> AdifRecord {
+    let mut record = AdifRecord::new();
+    push_field(&mut record, "CALL", &log.call.to_uppercase());
+
+    if let Some(time) = NaiveDateTime::from_timestamp_opt(log.time, 0) {
+        push_field(&mut record, "QSO_DATE", &time.format("%Y%m%d").to_string());
+        push_field(&mut record, "TIME_ON", &time.format("%H%M%S").to_string());
+    }
+
+    if let Some(position) = log.position() {
+        // ADIF grid square has at most 8 characters, rest of 10 character locator goes to its extension.
+        let qth = position.to_qth_precision(log.locator.len());
+        push_field(&mut record, "GRIDSQUARE", &qth.chars().take(8).collect::<String>());
+        if qth.len() > 8 {
+            push_field(&mut record, "GRIDSQUARE_EXT", &qth[8..]);
+        }
+    }
+
+    if let Some(code) = log.code.as_ref() {
+        push_field(&mut record, "SRX_STRING", code);
+    }
+
+    if let Some(frequency) = log.frequency {
+        push_field(&mut record, "FREQ", &band::format_frequency(frequency));
+    }
+    for (name, value) in [("BAND", &log.band), ("MODE", &log.mode), ("SUBMODE", &log.submode), ("RST_SENT", &log.rst_sent), ("RST_RCVD", &log.rst_rcvd)] {
+        if let Some(value) = value {
+            push_field(&mut record, name, value);
+        }
+    }
+    for (name, value) in [("STX", log.serial_sent), ("SRX", log.serial_rcvd)] {
+        if let Some(value) = value {
+            push_field(&mut record, name, &value.to_string());
+        }
+    }
+
+    if let Some(notes) = log.notes.as_ref() {
+        push_field(&mut record, "COMMENT", notes);
+    }
+
+    let (station_call, station_locator) = match (station, race) {
+        (Some(station), _) => (&station.call, &station.locator),
+        (None, Some(race)) => (&race.my_call, &race.my_location),
+        (None, None) => (&config.own_call, &config.own_locator),
+    };
+    let (antenna, power) = station.map_or((String::new(), config.default_power.clone()), |s| (s.antenna.clone(), s.power.clone()));
+    for (name, value) in [
+        ("STATION_CALLSIGN", station_call.to_uppercase()),
+        ("MY_GRIDSQUARE", station_locator.clone()),
+        ("MY_NAME", config.name.clone()),
+        ("MY_ANTENNA", antenna),
+        ("TX_PWR", power),
+    ] {
+        if !value.is_empty() {
+            push_field(&mut record, name, &value);
+        }
+    }
+
+    // Fields kept from import which we do not understand are written back as they were.
+    let extra = log.extra_fields.as_ref().and_then(|v| serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(v).ok());
+    for (name, value) in extra.unwrap_or_default() {
+        if let Some(value) = value.as_str() {
+            if !record.iter().any(|(field, _)| *field == name) {
+                push_field(&mut record, &name, value);
+            }
+        }
+    }
+    record
+}
+
+fn header_fields() -> AdifRecord {
+    let created = chrono::Utc::now().format("%Y%m%d %H%M%S").to_string();
+    vec!(
+        ("ADIF_VER".to_string(), ADIF_VERSION.to_string()),
+        ("CREATED_TIMESTAMP".to_string(), created),
+        ("PROGRAMID".to_string(), PROGRAM_ID.to_string()),
+        ("PROGRAMVERSION".to_string(), PROGRAM_VERSION.to_string()),
+    )
+}
+
+
+/// Latin letters with diacritics and their plain ASCII forms, for characters ADI can not carry.
+const TRANSLITERATIONS :[(&str, &str); 29] = [
+    ("ÀÁÂÃÄÅĀĂĄ", "A"), ("àáâãäåāăą", "a"), ("ÇĆČ", "C"), ("çćč", "c"), ("ĎĐ", "D"), ("ďđ", "d"),
+    ("ÈÉÊËĒĘĚ", "E"), ("èéêëēęě", "e"), ("ÌÍÎÏĪ", "I"), ("ìíîïī", "i"), ("ĹĽŁ", "L"), ("ĺľł", "l"),
+    ("ÑŃŇ", "N"), ("ñńň", "n"), ("ÒÓÔÕÖØŌŐ", "O"), ("òóôõöøōő", "o"), ("ŔŘ", "R"), ("ŕř", "r"),
+    ("ŚŠŞ", "S"), ("śšş", "s"), ("ŤŢ", "T"), ("ťţ", "t"), ("ÙÚÛÜŪŮŰ", "U"), ("ùúûüūůű", "u"),
+    ("ÝŸ", "Y"), ("ýÿ", "y"), ("ŹŻŽ", "Z"), ("źżž", "z"), ("ß", "ss"),
+];
+
+/// ADI fields may hold only ASCII, so lengths count characters. Letters with diacritics lose them,
+/// anything else outside ASCII becomes '?'. ADX keeps values in UTF-8.
+fn to_adi_ascii(value :&str) -> String {
+    value.chars().map(|c| {
+        if c.is_ascii() {
+            return c.to_string();
+        }
+        TRANSLITERATIONS.iter()
+            .find(|(from, _)| from.contains(c))
+            .map_or("?".to_string(), |(_, to)| to.to_string())
+    }).collect()
+}
+
+fn write_adi_field(out :&mut impl Write, name :&str, value :&str) -> std::io::Result<()> {
+    let value = to_adi_ascii(value);
+    write!(out, "<{}:{}>{} ", name, value.len(), value)
+}
+
+pub fn write_adi(out :&mut impl Write, records :&[AdifRecord]) -> std::io::Result<()> {
+    // Header text must not start with '<' so readers know there is a header at all.
+    writeln!(out, "RadioLog ADIF export")?;
+    for (name, value) in header_fields() {
+        write_adi_field(out, &name, &value)?;
+        writeln!(out)?;
+    }
+    writeln!(out, "<EOH>")?;
+
+    for record in records {
+        writeln!(out)?;
+        for (name, value) in record {
+            write_adi_field(out, name, value)?;
+        }
+        write!(out, "<EOR>")?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+
+fn escape_xml(value :&str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn write_adx(out :&mut impl Write, records :&[AdifRecord]) -> std::io::Result<()> {
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<ADX>")?;
+    writeln!(out, "  <HEADER>")?;
+    for (name, value) in header_fields() {
+        writeln!(out, "    <{0}>{1}</{0}>", name, escape_xml(&value))?;
+    }
+    writeln!(out, "  </HEADER>")?;
+
+    writeln!(out, "  <RECORDS>")?;
+    for record in records {
+        writeln!(out, "    <RECORD>")?;
+        for (name, value) in record {
+            writeln!(out, "      <{0}>{1}</{0}>", name, escape_xml(value))?;
+        }
+        writeln!(out, "    </RECORD>")?;
+    }
+    writeln!(out, "  </RECORDS>")?;
+    writeln!(out, "</ADX>")?;
+    Ok(())
+}
+
+
+fn read_tag(data :&[u8], start :usize) -> Option<(String, usize)> {
+    let end = data[start..].iter().position(|c| *c == b'>')? + start;
+    Some((String::from_utf8_lossy(&data[start + 1..end]).to_string(), end + 1))
+}
+
+pub fn parse_adi(content :&[u8]) -> Result<Vec<AdifRecord>, String> {
+    let mut pos = 0;
+
+    // Free text header is present only when file does not start with a tag.
+    if content.first() != Some(&b'<') {
+        let upper = content.to_ascii_uppercase();
+        pos = match upper.windows(5).position(|w| w == b"<EOH>") {
+            Some(index) => index + 5,
+            None => return Err("Missing <EOH> header terminator".to_string()),
+        };
+    }
+
+    let mut records = vec!();
+    let mut current = AdifRecord::new();
+    while let Some(offset) = content[pos..].iter().position(|c| *c == b'<') {
+        let tag_start = pos + offset;
+        let (tag, data_start) = read_tag(content, tag_start)
+            .ok_or(format!("Unterminated tag at byte {}", tag_start))?;
+
+        let mut parts = tag.split(':');
+        let name = parts.next().unwrap_or_default().trim().to_uppercase();
+        match name.as_str() {
+            "EOH" => current.clear(),
+            "EOR" => {
+                if !current.is_empty() {
+                    records.push(std::mem::take(&mut current));
+                }
+            },
+            _ => {
+                let length :usize = match parts.next() {
+                    Some(len) => len.trim().parse().map_err(|_| format!("Invalid length of field {}", name))?,
+                    None => 0,
+                };
+                let data_end = match data_start.checked_add(length) {
+                    Some(end) if end <= content.len() => end,
+                    _ => return Err(format!("Field {} exceeds end of file", name)),
+                };
+                let value = String::from_utf8_lossy(&content[data_start..data_end]).trim().to_string();
+                current.push((name, value));
+                pos = data_end;
+                continue;
+            }
+        }
+        pos = data_start;
+    }
+    Ok(records)
+}
+
+
+fn unescape_xml(value :&str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        result.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let Some(end) = rest.find(';') else { break; };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None
+        };
+        match decoded {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn xml_attribute(tag :&str, name :&str) -> Option<String> {
+    let start = tag.find(&format!("{}=", name))? + name.len() + 1;
+    let quote = tag[start..].chars().next()?;
+    let value_end = tag[start + 1..].find(quote)? + start + 1;
+    Some(unescape_xml(&tag[start + 1..value_end]))
+}
+
+fn parse_adx_record(content :&str) -> Result<AdifRecord, String> {
+    let mut record = AdifRecord::new();
+    let mut rest = content;
+
+    while let Some(open) = rest.find('<') {
+        let close = rest[open..].find('>').ok_or("Unterminated element")? + open;
+        let tag = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        let element = tag.split_whitespace().next().unwrap_or_default().to_ascii_uppercase();
+        let name = match element.as_str() {
+            "APP" => format!(
+                "APP_{}_{}",
+                xml_attribute(tag, "PROGRAMID").unwrap_or_default(),
+                xml_attribute(tag, "FIELDNAME").unwrap_or_default()
+            ).to_uppercase(),
+            "USERDEF" => xml_attribute(tag, "FIELDNAME").unwrap_or_default().to_uppercase(),
+            _ => element.clone(),
+        };
+
+        if tag.ends_with('/') {
+            continue;
+        }
+
+        let end_tag = format!("</{}", element);
+        // ASCII uppercase keeps byte offsets valid for the original text.
+        let value_end = rest.to_ascii_uppercase().find(&end_tag).ok_or(format!("Missing end of element {}", element))?;
+        let value = unescape_xml(rest[..value_end].trim());
+        rest = &rest[value_end..];
+        rest = &rest[rest.find('>').map_or(rest.len(), |i| i + 1)..];
+
+        if !name.is_empty() {
+            record.push((name, value));
+        }
+    }
+    Ok(record)
+}
+
+pub fn parse_adx(content :&str) -> Result<Vec<AdifRecord>, String> {
+    let upper = content.to_ascii_uppercase();
+    let mut records = vec!();
+    let mut pos = upper.find("<RECORDS").ok_or("Missing RECORDS element")?;
+
+    while let Some(start) = upper[pos..].find("<RECORD>") {
+        let start = pos + start + "<RECORD>".len();
+        let end = upper[start..].find("</RECORD>").ok_or("Unterminated RECORD element")? + start;
+        records.push(parse_adx_record(&content[start..end])?);
+        pos = end;
+    }
+    Ok(records)
+}
+
+
+fn record_value<'a>(record :&'a AdifRecord, name :&str) -> Option<&'a str> {
+    record.iter()
+        .find(|(field, value)| field == name && !value.is_empty())
+        .map(|(_, value)| value.as_str())
+}
+
+fn parse_qso_time(date :&str, time :&str) -> Result<i64, String> {
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y%m%d")
+        .map_err(|_| format!("Invalid QSO_DATE '{}'", date))?;
+
+    let time_format = if time.len() == 4 { "%H%M" } else { "%H%M%S" };
+    let time = chrono::NaiveTime::parse_from_str(time, time_format)
+        .map_err(|_| format!("Invalid TIME_ON '{}'", time))?;
+
+    Ok(date.and_time(time).timestamp())
+}
+
+/// Fields which are stored into dedicated columns of LogEntry. Everything else is kept in `extra_fields`.
+const MAPPED_FIELDS :[&str; 16] = [
+    "CALL", "QSO_DATE", "TIME_ON", "GRIDSQUARE", "GRIDSQUARE_EXT", "SRX_STRING", "SRX", "COMMENT", "NOTES",
+    "FREQ", "BAND", "MODE", "SUBMODE", "RST_SENT", "RST_RCVD", "STX"
+];
+
+pub fn record_to_log(record :&AdifRecord) -> Result<LogEntry, String> {
+    let call = record_value(record, "CALL").ok_or("Record without CALL")?;
+    let date = record_value(record, "QSO_DATE").ok_or(format!("Record of {} without QSO_DATE", call))?;
+    let time = record_value(record, "TIME_ON").ok_or(format!("Record of {} without TIME_ON", call))?;
+
+    let notes = [record_value(record, "COMMENT"), record_value(record, "NOTES")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" / ");
+
+    let extra :serde_json::Map<String, serde_json::Value> = record.iter()
+        .filter(|(name, _)| !MAPPED_FIELDS.contains(&name.as_str()))
+        .map(|(name, value)| (name.clone(), serde_json::Value::String(value.clone())))
+        .collect();
+
+    let frequency = match record_value(record, "FREQ") {
+        Some(freq) => Some(freq.parse::<f64>().map_err(|_| format!("Invalid FREQ '{}'", freq))?),
+        None => None,
+    };
+    // Extension carries last two characters of 10 character locator.
+    let locator = [record_value(record, "GRIDSQUARE"), record_value(record, "GRIDSQUARE_EXT")]
+        .into_iter()
+        .flatten()
+        .collect::<String>();
+
+    let serial = |name :&str| -> Result<Option<i64>, String> {
+        match record_value(record, name) {
+            Some(value) => value.parse().map(Some).map_err(|_| format!("Invalid {} '{}'", name, value)),
+            None => Ok(None),
+        }
+    };
+
+    let mut log = LogEntry {
+        time: parse_qso_time(date, time)?,
+        call: call.to_uppercase(),
+        locator,
+        code: record_value(record, "SRX_STRING").map(|v| v.to_string()),
+        notes: (!notes.is_empty()).then_some(notes),
+        extra_fields: (!extra.is_empty()).then(|| serde_json::Value::Object(extra).to_string()),
+        band: record_value(record, "BAND").map(|v| v.to_lowercase()),
+        mode: record_value(record, "MODE").map(|v| v.to_uppercase()),
+        submode: record_value(record, "SUBMODE").map(|v| v.to_uppercase()),
+        rst_sent: record_value(record, "RST_SENT").map(|v| v.to_string()),
+        rst_rcvd: record_value(record, "RST_RCVD").map(|v| v.to_string()),
+        serial_sent: serial("STX")?,
+        serial_rcvd: serial("SRX")?,
+        ..Default::default()
+    };
+    log.set_frequency(frequency.map(|mhz| (mhz * 1e6).round() as i64));
+    Ok(log)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_adi_with_header() {
+        let content = b"Exported log <ADIF_VER:5>3.1.4 <EOH>\n<CALL:6>OK1ABC <GRIDSQUARE:6>JO70fd <QSO_DATE:8>20240601 <TIME_ON:4>1230 <EOR>\n";
+        let records = parse_adi(content).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(record_value(&records[0], "CALL"), Some("OK1ABC"));
+        assert_eq!(record_value(&records[0], "GRIDSQUARE"), Some("JO70fd"));
+    }
+
+    #[test]
+    fn rejects_adi_field_longer_than_file() {
+        assert!(parse_adi(b"<CALL:10>OK1ABC").is_err());
+        assert!(parse_adi(format!("<CALL:{}>OK1ABC<EOR>", usize::MAX).as_bytes()).is_err());
+    }
+
+    #[test]
+    fn parses_adx_with_characters_changing_length_when_uppercased() {
+        let content = "<?xml version=\"1.0\"?><ADX><HEADER></HEADER><RECORDS>\
+            <RECORD><CALL>DL1ABC</CALL><COMMENT>Grüße aus Straße, ﬁne</COMMENT><QSO_DATE>20240601</QSO_DATE></RECORD>\
+            <RECORD><CALL>OK1ABC</CALL><COMMENT>Tom &amp; Jerry</COMMENT></RECORD>\
+            </RECORDS></ADX>";
+        let records = parse_adx(content).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(record_value(&records[0], "COMMENT"), Some("Grüße aus Straße, ﬁne"));
+        assert_eq!(record_value(&records[0], "QSO_DATE"), Some("20240601"));
+        assert_eq!(record_value(&records[1], "COMMENT"), Some("Tom & Jerry"));
+    }
+
+    #[test]
+    fn converts_record_to_log() {
+        let records = parse_adi(b"<CALL:6>ok1abc <QSO_DATE:8>20240601 <TIME_ON:6>123000 <FREQ:7>144.300 <MODE:3>SSB <MY_RIG:3>FT8 <EOR>").unwrap();
+        let log = record_to_log(&records[0]).unwrap();
+        assert_eq!(log.call, "OK1ABC");
+        assert_eq!(log.time, 1717245000);
+        assert_eq!(log.frequency, Some(144_300_000));
+        assert_eq!(log.band.as_deref(), Some("2m"));
+        assert!(log.extra_fields.unwrap().contains("MY_RIG"));
+    }
+
+    #[test]
+    fn writes_non_ascii_adi_values_as_ascii() {
+        let record :AdifRecord = vec!(
+            ("NAME".to_string(), "Jiří Straße".to_string()),
+            ("QTH".to_string(), "Łódź 東京".to_string()),
+        );
+        let mut out = Vec::new();
+        write_adi(&mut out, &[record]).unwrap();
+        let content = String::from_utf8(out).unwrap();
+        assert!(content.contains("<NAME:12>Jiri Strasse "), "{}", content);
+        assert!(content.contains("<QTH:7>Lodz ?? "), "{}", content);
+
+        let records = parse_adi(content.as_bytes()).unwrap();
+        assert_eq!(record_value(&records[0], "NAME"), Some("Jiri Strasse"));
+        assert_eq!(record_value(&records[0], "QTH"), Some("Lodz ??"));
+    }
+
+    #[test]
+    fn keeps_locator_extension() {
+        let log = LogEntry { call: "OK1ABC".to_string(), locator: "JO70fb12ab".to_string(), time: 1717245000, ..Default::default() };
+        let record = log_to_record(&log, None, None, &ConfigData::default());
+        assert_eq!(record_value(&record, "GRIDSQUARE"), Some("JO70fb12"));
+        assert_eq!(record_value(&record, "GRIDSQUARE_EXT"), Some("ab"));
+
+        let imported = record_to_log(&record).unwrap();
+        assert_eq!(imported.locator, "JO70fb12ab");
+        assert!(imported.extra_fields.is_none_or(|extra| !extra.contains("GRIDSQUARE_EXT")));
+    }
+}
@@ -0,0 +1,111 @@
+use std::io::Write;
+
+use chrono::NaiveDateTime;
+
+use crate::data::{Data, LogEntry, Race, band};
+use super::{ExportField, field_value};
+
+
+const CREATED_BY :&str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+
+
+/// Header values which race does not store and user has to fill in before export.
+pub fn header_fields(data :&Data, race :&Race) -> Vec<ExportField> {
+    let score = data.race_score(Some(race.id)).total().round() as i64;
+    vec!(
+        ExportField::new("CONTEST", "Contest", &race.name.to_uppercase().replace(' ', "-")),
+        ExportField::new("LOCATION", "Location", "DX"),
+        ExportField::new("CATEGORY-OPERATOR", "Category operator", "SINGLE-OP"),
+        ExportField::new("CATEGORY-BAND", "Category band", "ALL"),
+        ExportField::new("CATEGORY-MODE", "Category mode", "MIXED"),
+        ExportField::new("CATEGORY-POWER", "Category power", "HIGH"),
+        ExportField::new("CATEGORY-STATION", "Category station", "FIXED"),
+        ExportField::new("CATEGORY-TRANSMITTER", "Category transmitter", "ONE"),
+        ExportField::new("CLAIMED-SCORE", "Claimed score", &score.to_string()),
+        ExportField::new("OPERATORS", "Operators", &data.resolve_call(None, Some(race.id)).to_uppercase()),
+        ExportField::new("NAME", "Name", &data.config().name),
+        ExportField::new("EMAIL", "Email", ""),
+        ExportField::new("QSO_FREQ", "Default QSO frequency (kHz or band)", "144"),
+        ExportField::new("QSO_MODE", "Default QSO mode (CW, PH, FM, RY, DG)", "PH"),
+        ExportField::new("EXCH_SENT", "Sent exchange after RST and Nr", &data.resolve_locator(None, Some(race.id)).to_uppercase()),
+    )
+}
+
+/// Bands from 50 MHz up are written as band designators instead of frequency in kHz.
+const VHF_BAND_DESIGNATORS :[(&str, &str); 17] = [
+    ("6m", "50"), ("4m", "70"), ("2m", "144"), ("1.25m", "222"), ("70cm", "432"), ("33cm", "902"),
+    ("23cm", "1.2G"), ("13cm", "2.3G"), ("9cm", "3.4G"), ("6cm", "5.7G"), ("3cm", "10G"),
+    ("1.25cm", "24G"), ("6mm", "47G"), ("4mm", "75G"), ("2.5mm", "122G"), ("2mm", "134G"), ("1mm", "241G"),
+];
+
+fn qso_frequency(log :&LogEntry) -> Option<String> {
+    let band = log.band.as_ref().and_then(|name| band::band_by_name(name));
+    if let Some((_, designator)) = band.and_then(|band| VHF_BAND_DESIGNATORS.iter().find(|(name, _)| *name == band.name)) {
+        return Some(designator.to_string());
+    }
+    log.frequency.map(|hz| (hz / 1000).to_string())
+}
+
+fn qso_mode(log :&LogEntry) -> Option<&'static str> {
+    let mode = log.mode.as_deref()?;
+    Some(match mode {
+        "CW" => "CW",
+        "SSB" | "USB" | "LSB" | "AM" => "PH",
+        "FM" => "FM",
+        "RTTY" => "RY",
+        _ => "DG",
+    })
+}
+
+fn qso_line(log :&LogEntry, my_call :&str, fields :&[ExportField]) -> String {
+    let time = NaiveDateTime::from_timestamp_opt(log.time, 0)
+        .map_or("0000-00-00 0000".to_string(), |t| t.format("%Y-%m-%d %H%M").to_string());
+
+    let serial = |serial :Option<i64>| serial.map_or(String::new(), |v| format!("{:03}", v));
+    let join = |values :Vec<String>| values.into_iter().filter(|v| !v.is_empty()).collect::<Vec<_>>().join(" ");
+
+    let exchange_sent = join(vec!(
+        log.rst_sent.clone().unwrap_or_default(),
+        serial(log.serial_sent),
+        field_value(fields, "EXCH_SENT").to_string(),
+    ));
+    let exchange_rcvd = join(vec!(
+        log.rst_rcvd.clone().unwrap_or_default(),
+        serial(log.serial_rcvd),
+        log.code.clone().unwrap_or_default(),
+        log.locator.to_uppercase(),
+    ));
+
+    format!(
+        "QSO: {:>5} {:<2} {} {:<13} {:<14} {:<13} {}",
+        qso_frequency(log).unwrap_or(field_value(fields, "QSO_FREQ").to_string()),
+        qso_mode(log).unwrap_or(field_value(fields, "QSO_MODE")),
+        time,
+        my_call.to_uppercase(),
+        exchange_sent,
+        log.call.to_uppercase(),
+        exchange_rcvd
+    )
+}
+
+pub fn write(out :&mut impl Write, data :&Data, race :&Race, fields :&[ExportField]) -> std::io::Result<usize> {
+    writeln!(out, "START-OF-LOG: 3.0")?;
+    writeln!(out, "CREATED-BY: {}", CREATED_BY)?;
+    writeln!(out, "CALLSIGN: {}", data.resolve_call(None, Some(race.id)).to_uppercase())?;
+    writeln!(out, "GRID-LOCATOR: {}", data.resolve_locator(None, Some(race.id)).to_uppercase())?;
+
+    // Only real Cabrillo tags are written, QSO_ and EXCH_ values are used for QSO lines.
+    for field in fields.iter().filter(|f| !f.key.starts_with("QSO_") && !f.key.starts_with("EXCH_")) {
+        if !field.value.is_empty() {
+            writeln!(out, "{}: {}", field.key, field.value)?;
+        }
+    }
+
+    let mut count = 0;
+    for log in data.race_logs(Some(race.id)) {
+        writeln!(out, "{}", qso_line(log, data.resolve_call(log.station_id, log.race_id), fields))?;
+        count += 1;
+    }
+    writeln!(out, "END-OF-LOG:")?;
+    Ok(count)
+}
@@ -0,0 +1,276 @@
+use std::{io::Write, collections::HashSet};
+
+use chrono::NaiveDateTime;
+
+use crate::data::{Data, LogEntry, Race, dxcc, scoring::Score, dupes::DupeChecker};
+use super::{ExportField, field_value};
+
+
+// Format description: https://www.ok2kkw.com/ediformat.htm (REG1TEST;1)
+
+/// Header tags user can edit before export. Rest of the header is computed from race and logs.
+const EDITABLE_TAGS :[(&str, &str); 15] = [
+    ("TName", "Contest name"),
+    ("TDate", "Date (from;to)"),
+    ("PSect", "Section"),
+    ("PBand", "Band"),
+    ("PExch", "Sent exchange"),
+    ("PClub", "Club"),
+    ("RName", "Responsible op. name"),
+    ("RCall", "Responsible op. call"),
+    ("MOpe1", "Operators"),
+    ("MOpe2", "Operators (cont.)"),
+    ("STXEq", "TX equipment"),
+    ("SPowe", "Power (W)"),
+    ("SRXEq", "RX equipment"),
+    ("SAnte", "Antenna"),
+    ("SAntH", "Antenna height (agl;asl)"),
+];
+
+/// Tags which are written empty as we do not track station addresses.
+const ADDRESS_TAGS :[&str; 9] = ["PAdr1", "PAdr2", "RAdr1", "RAdr2", "RPoCo", "RCity", "RCoun", "RPhon", "RHBBS"];
+
+
+pub fn header_fields(data :&Data, race :&Race) -> Vec<ExportField> {
+    let dates :Vec<String> = data.race_logs(Some(race.id))
+        .filter_map(|log| NaiveDateTime::from_timestamp_opt(log.time, 0))
+        .map(|time| time.format("%Y%m%d").to_string())
+        .collect();
+    let date_range = match (dates.iter().min(), dates.iter().max()) {
+        (Some(from), Some(to)) => format!("{};{}", from, to),
+        _ => String::new(),
+    };
+
+    let mut fields :Vec<ExportField> = EDITABLE_TAGS.iter()
+        .map(|(tag, label)| {
+            let value = match *tag {
+                "TName" => race.name.clone(),
+                "TDate" => date_range.clone(),
+                "PSect" => "SINGLE".to_string(),
+                "PBand" => "144 MHz".to_string(),
+                "RCall" | "MOpe1" => data.resolve_call(None, Some(race.id)).to_uppercase(),
+                "RName" => data.config().name.clone(),
+                "SPowe" => data.config().default_power.clone(),
+                _ => String::new(),
+            };
+            ExportField::new(tag, label, &value)
+        })
+        .collect();
+
+    fields.push(ExportField::new("QSO_MODE", "Default mode code (1 SSB, 2 CW, 6 FM)", "1"));
+    fields.push(ExportField::new("RST_SENT", "Default sent RST", "59"));
+    fields
+}
+
+
+/// Evaluated QSO line with claimed points.
+struct EdiQso<'a> {
+    log :&'a LogEntry,
+    distance_km :Option<f64>,
+    points :i64,
+    new_wwl :bool,
+    new_dxcc :bool,
+    duplicate :bool,
+}
+
+/// QSO points are counted by scoring rules of the race, so they match score shown in the application.
+fn evaluate<'a>(data :&'a Data, race :&Race) -> Vec<EdiQso<'a>> {
+    let rules = data.scoring_rules(Some(race.id));
+    let dupes = DupeChecker::new(data, Some(race.id), None);
+    let mut squares = HashSet::new();
+    let mut countries = HashSet::new();
+
+    let mut logs :Vec<&LogEntry> = data.race_logs(Some(race.id)).collect();
+    logs.sort_by_key(|log| log.time);
+
+    logs.into_iter().map(|log| {
+        let duplicate = log.dupe || dupes.find(log).is_some();
+        let square :String = log.locator.to_uppercase().chars().take(4).collect();
+        let distance_km = data.log_distance_km(log);
+        EdiQso {
+            log,
+            distance_km,
+            points: if duplicate { 0 } else { rules.qso_points(distance_km).round() as i64 },
+            new_wwl: !duplicate && square.len() == 4 && squares.insert(square),
+            new_dxcc: !duplicate && countries.insert(dxcc::entity_key(&log.call)),
+            duplicate,
+        }
+    }).collect()
+}
+
+fn mode_code(log :&LogEntry) -> Option<&'static str> {
+    Some(match log.mode.as_deref()? {
+        "SSB" | "USB" | "LSB" => "1",
+        "CW" => "2",
+        "AM" => "5",
+        "FM" => "6",
+        "RTTY" => "7",
+        "SSTV" => "8",
+        "ATV" => "9",
+        _ => "0",
+    })
+}
+
+fn qso_line(qso :&EdiQso, serial :usize, fields :&[ExportField]) -> String {
+    let log = qso.log;
+    let time = NaiveDateTime::from_timestamp_opt(log.time, 0)
+        .map_or("000000;0000".to_string(), |t| t.format("%y%m%d;%H%M").to_string());
+
+    // Numeric code is received serial number for logs without one, anything else is received exchange.
+    let code = log.code.clone().unwrap_or_default();
+    let (rcvd_number, rcvd_exchange) = match (log.serial_rcvd, code.parse::<i64>()) {
+        (Some(number), _) => (Some(number), code),
+        (None, Ok(number)) => (Some(number), String::new()),
+        (None, Err(_)) => (None, code),
+    };
+
+    format!(
+        "{};{};{};{};{:03};{};{};{};{};{};;{};{};{}",
+        time,
+        log.call.to_uppercase(),
+        mode_code(log).unwrap_or(field_value(fields, "QSO_MODE")),
+        log.rst_sent.as_deref().unwrap_or(field_value(fields, "RST_SENT")),
+        log.serial_sent.unwrap_or(serial as i64),
+        log.rst_rcvd.as_deref().unwrap_or_default(),
+        rcvd_number.map_or(String::new(), |v| format!("{:03}", v)),
+        rcvd_exchange,
+        log.locator.to_uppercase(),
+        qso.points,
+        if qso.new_wwl { "N" } else { "" },
+        if qso.new_dxcc { "N" } else { "" },
+        if qso.duplicate { "D" } else { "" },
+    )
+}
+
+pub fn write(out :&mut impl Write, data :&Data, race :&Race, fields :&[ExportField]) -> std::io::Result<usize> {
+    let qsos = evaluate(data, race);
+
+    let rules = data.scoring_rules(Some(race.id));
+    let mut score = Score::new(&rules);
+    for qso in qsos.iter().filter(|q| !q.duplicate) {
+        score.add(&rules, qso.log, qso.distance_km);
+    }
+    let wwl_count = qsos.iter().filter(|q| q.new_wwl).count();
+    let dxcc_count = qsos.iter().filter(|q| q.new_dxcc).count();
+    let odx = qsos.iter()
+        .filter(|q| !q.duplicate)
+        .filter_map(|q| Some((q, q.distance_km?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+    writeln!(out, "[REG1TEST;1]")?;
+    for tag in ["TName", "TDate"] {
+        writeln!(out, "{}={}", tag, field_value(fields, tag))?;
+    }
+    writeln!(out, "PCall={}", data.resolve_call(None, Some(race.id)).to_uppercase())?;
+    writeln!(out, "PWWLo={}", data.resolve_locator(None, Some(race.id)).to_uppercase())?;
+    for (tag, _) in EDITABLE_TAGS.iter().skip(2) {
+        writeln!(out, "{}={}", tag, field_value(fields, tag))?;
+    }
+    for tag in ADDRESS_TAGS {
+        writeln!(out, "{}=", tag)?;
+    }
+
+    writeln!(out, "CQSOs={};1", score.qsos)?;
+    writeln!(out, "CQSOP={}", score.points.round() as i64)?;
+    writeln!(out, "CWWLs={};0;1", wwl_count)?;
+    writeln!(out, "CWWLB=0")?;
+    writeln!(out, "CExcs=0;0;1")?;
+    writeln!(out, "CExcB=0")?;
+    writeln!(out, "CDXCs={};0;1", dxcc_count)?;
+    writeln!(out, "CDXCB=0")?;
+    writeln!(out, "CToSc={}", score.total().round() as i64)?;
+    match odx {
+        Some((odx, km)) => writeln!(out, "CODXC={};{};{}", odx.log.call.to_uppercase(), odx.log.locator.to_uppercase(), km.round() as i64)?,
+        None => writeln!(out, "CODXC=")?,
+    }
+
+    writeln!(out, "[Remarks]")?;
+    writeln!(out, "[QSORecords;{}]", qsos.len())?;
+    for (index, qso) in qsos.iter().enumerate() {
+        writeln!(out, "{}", qso_line(qso, index + 1, fields))?;
+    }
+    Ok(qsos.len())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, path::Path};
+
+    use super::*;
+    use crate::{database::Database, data::StationProfile};
+
+    fn export(data :&Data, race :&Race) -> String {
+        let mut out = Vec::new();
+        write(&mut out, data, race, &header_fields(data, race)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn takes_call_and_locator_from_race_station() {
+        let db = RefCell::new(Database::new(Path::new(":memory:")).unwrap());
+        let mut data = Data::new(&db).unwrap();
+        data.stations.add(StationProfile { call: "OK1KHL".to_string(), locator: "JO70fb".to_string(), ..Default::default() }).unwrap();
+        let station_id = data.stations.iter().next().unwrap().id;
+        data.races.add(Race { name: "VHF".to_string(), station_id: Some(station_id), ..Default::default() }).unwrap();
+        let race = data.races.iter().next().unwrap().clone();
+        data.logs.add(LogEntry {
+            call: "DL1XYZ".to_string(),
+            locator: "JO62qm".to_string(),
+            race_id: Some(race.id),
+            ..Default::default()
+        }).unwrap();
+
+        let edi = export(&data, &race);
+        assert!(edi.contains("PCall=OK1KHL\n"), "{}", edi);
+        assert!(edi.contains("PWWLo=JO70FB\n"), "{}", edi);
+        assert!(edi.contains("CODXC=DL1XYZ;JO62QM;"), "{}", edi);
+        assert!(!edi.contains("CToSc=0\n"), "{}", edi);
+    }
+
+    #[test]
+    fn claims_score_of_scoring_rules() {
+        let db = RefCell::new(Database::new(Path::new(":memory:")).unwrap());
+        let mut data = Data::new(&db).unwrap();
+        data.races.add(Race { my_location: "JO70fb".to_string(), scoring: "qso=1 km=1".to_string(), ..Default::default() }).unwrap();
+        let race = data.races.iter().next().unwrap().clone();
+        // 283.5 and 291.7 km.
+        data.logs.add_all(vec!(
+            LogEntry { call: "DL1XYZ".to_string(), locator: "JO62qm".to_string(), race_id: Some(race.id), time: 1, ..Default::default() },
+            LogEntry { call: "OM3AAA".to_string(), locator: "JN88nc".to_string(), race_id: Some(race.id), time: 2, ..Default::default() },
+        )).unwrap();
+
+        let edi = export(&data, &race);
+        let score = data.race_score(Some(race.id)).total().round() as i64;
+        assert_eq!(score, 1 + 284 + 1 + 292);
+        assert!(edi.contains(&format!("CToSc={}\n", score)), "{}", edi);
+        assert!(edi.contains("CODXC=OM3AAA;JN88NC;292\n"), "{}", edi);
+        assert!(edi.contains(";JO62QM;285;"), "{}", edi);
+    }
+
+    #[test]
+    fn marks_duplicates_by_race_rule() {
+        let db = RefCell::new(Database::new(Path::new(":memory:")).unwrap());
+        let mut data = Data::new(&db).unwrap();
+        data.races.add(Race { my_location: "JO70fb".to_string(), dupe_rule: "band+mode".to_string(), ..Default::default() }).unwrap();
+        let race = data.races.iter().next().unwrap().clone();
+        let log = |time :i64, mode :&str| LogEntry {
+            call: "DL1XYZ".to_string(),
+            locator: "JO62qm".to_string(),
+            band: Some("2m".to_string()),
+            mode: Some(mode.to_string()),
+            race_id: Some(race.id),
+            time,
+            ..Default::default()
+        };
+        data.logs.add_all(vec!(log(1, "SSB"), log(2, "CW"), log(3, "SSB"))).unwrap();
+
+        let edi = export(&data, &race);
+        let flags :Vec<&str> = edi.lines()
+            .filter(|line| line.contains(";DL1XYZ;"))
+            .map(|line| line.rsplit(';').next().unwrap_or_default())
+            .collect();
+        assert_eq!(flags, ["", "", "D"]);
+        assert!(edi.contains("CQSOs=2;1\n"), "{}", edi);
+    }
+}
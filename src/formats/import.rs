@@ -0,0 +1,88 @@
+use std::{path::{Path, PathBuf}, collections::HashMap};
+
+use crate::{data::{Data, LogEntry}, app_errors::AppError};
+use super::adif;
+
+
+/// Maximal difference of QSO times (in seconds) for two logs with the same call to be considered one contact.
+const DUPLICATE_TIME_WINDOW :i64 = 10 * 60;
+
+
+/// Parsed content of import file split into logs which will be stored and the ones which are skipped.
+pub struct ImportPreview {
+    pub path :PathBuf,
+    pub race_id :Option<i64>,
+
+    pub new_logs :Vec<LogEntry>,
+    pub duplicates :Vec<LogEntry>,
+    pub errors :Vec<String>,
+}
+
+
+fn is_same_contact(time :i64, locator :&str, other_time :i64, other_locator :&str) -> bool {
+    (time - other_time).abs() <= DUPLICATE_TIME_WINDOW
+        && (locator.is_empty() || other_locator.is_empty() || locator == other_locator)
+}
+
+impl ImportPreview {
+    pub fn from_file(path :&Path, data :&Data, race_id :Option<i64>) -> Result<Self, AppError> {
+        let content = std::fs::read(path)?;
+
+        let is_adx = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("adx"))
+            || content.starts_with(b"<?xml");
+        let records = match is_adx {
+            true => adif::parse_adx(&String::from_utf8_lossy(&content)),
+            false => adif::parse_adi(&content),
+        }.map_err(AppError::ImportError)?;
+
+        // Known contacts by call, used both against database and against records earlier in the same file.
+        let mut known :HashMap<String, Vec<(i64, String)>> = HashMap::new();
+        for log in data.logs.iter() {
+            known.entry(log.call.to_uppercase())
+                .or_default()
+                .push((log.time, log.locator.to_uppercase()));
+        }
+
+        let mut preview = ImportPreview {
+            path: path.to_path_buf(),
+            race_id,
+            new_logs: vec!(),
+            duplicates: vec!(),
+            errors: vec!(),
+        };
+
+        for (index, record) in records.iter().enumerate() {
+            let mut log = match adif::record_to_log(record) {
+                Ok(log) => log,
+                Err(err) => {
+                    preview.errors.push(format!("Record {}: {}", index + 1, err));
+                    continue;
+                }
+            };
+            log.race_id = race_id;
+            log.station_id = data.current_station_id(race_id);
+
+            let time = log.time;
+            let locator = log.locator.to_uppercase();
+            let seen = known.entry(log.call.clone()).or_default();
+            if seen.iter().any(|(other_time, other_locator)| is_same_contact(time, &locator, *other_time, other_locator)) {
+                preview.duplicates.push(log);
+            } else {
+                seen.push((time, locator));
+                preview.new_logs.push(log);
+            }
+        }
+        Ok(preview)
+    }
+
+    pub fn total(&self) -> usize {
+        self.new_logs.len() + self.duplicates.len() + self.errors.len()
+    }
+
+    /// Stores all new logs in single transaction. Returns number of imported logs.
+    pub fn commit(self, data :&mut Data) -> Result<usize, AppError> {
+        let count = self.new_logs.len();
+        data.logs.add_all(self.new_logs)?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use crate::{data::Data, app_errors::AppError};
+
+pub mod adif;
+use adif::AdifFormat;
+
+mod cabrillo;
+mod edi;
+
+mod import;
+pub use import::ImportPreview;
+
+
+
+/// Additional value required by export format which is not stored in database.
+#[derive(Debug, Clone)]
+pub struct ExportField {
+    pub key :&'static str,
+    pub label :&'static str,
+    pub value :String,
+}
+
+impl ExportField {
+    pub fn new(key :&'static str, label :&'static str, value :&str) -> Self {
+        Self {
+            key,
+            label,
+            value: value.to_string(),
+        }
+    }
+}
+
+fn field_value<'a>(fields :&'a [ExportField], key :&str) -> &'a str {
+    fields.iter().find(|f| f.key == key).map_or("", |f| f.value.as_str())
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Adif(AdifFormat),
+    Cabrillo,
+    Edi,
+}
+
+impl ExportFormat {
+    pub fn from_path(path :&Path) -> Option<ExportFormat> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "adi" | "adif" => Some(ExportFormat::Adif(AdifFormat::Adi)),
+            "adx" => Some(ExportFormat::Adif(AdifFormat::Adx)),
+            "cbr" | "log" => Some(ExportFormat::Cabrillo),
+            "edi" => Some(ExportFormat::Edi),
+            _ => None
+        }
+    }
+
+    pub fn supported_extensions() -> &'static str {
+        ".adi, .adx, .cbr, .edi"
+    }
+
+    /// Values user can review before export. Formats which need race return no fields when race is not set.
+    pub fn fields(&self, data :&Data, race_id :Option<i64>) -> Vec<ExportField> {
+        let race = race_id.and_then(|id| data.races.get(id));
+        match (self, race) {
+            (ExportFormat::Cabrillo, Some(race)) => cabrillo::header_fields(data, race),
+            (ExportFormat::Edi, Some(race)) => edi::header_fields(data, race),
+            _ => vec!()
+        }
+    }
+
+    /// Writes logs of given race (or all logs when None) into file. Returns number of exported logs.
+    pub fn export(&self, data :&Data, race_id :Option<i64>, fields :&[ExportField], path :&Path) -> Result<usize, AppError> {
+        let race = race_id.and_then(|id| data.races.get(id));
+
+        // Render whole file first so failed export does not leave partially written file behind.
+        let mut out = Vec::new();
+        let count = match self {
+            ExportFormat::Adif(format) => {
+                let records :Vec<_> = data.race_logs(race_id)
+                    .map(|log| adif::log_to_record(
+                        log,
+                        data.station_of(log.station_id, log.race_id),
+                        log.race_id.and_then(|id| data.races.get(id)),
+                        data.config()
+                    ))
+                    .collect();
+
+                match format {
+                    AdifFormat::Adi => adif::write_adi(&mut out, &records)?,
+                    AdifFormat::Adx => adif::write_adx(&mut out, &records)?,
+                }
+                records.len()
+            },
+            ExportFormat::Cabrillo => {
+                let race = race.ok_or(AppError::ExportError("Cabrillo export requires race to be selected".to_string()))?;
+                cabrillo::write(&mut out, data, race, fields)?
+            },
+            ExportFormat::Edi => {
+                let race = race.ok_or(AppError::ExportError("EDI export requires race to be selected".to_string()))?;
+                edi::write(&mut out, data, race, fields)?
+            },
+        };
+
+        std::fs::write(path, out)?;
+        Ok(count)
+    }
+}
@@ -9,6 +9,7 @@ mod ui_handler;
 mod app_context;
 mod database;
 mod app_errors;
+mod formats;
 
 use app::App;
 use app_context::AppContext;
@@ -16,7 +17,7 @@ use app_errors::AppError;
 
 
 
-use std::{io, cell::RefCell};
+use std::{io::{self, Write}, cell::RefCell, path::PathBuf};
 use ratatui::{
     backend::CrosstermBackend,
     Terminal
@@ -36,9 +37,37 @@ fn reset_terminal() -> CrosstermResult<()> {
     Ok(())
 }
 
+/// Asked before terminal switches into raw mode so plain stdin / stdout can be used.
+fn confirm_read_only(err :&AppError) -> Result<bool, AppError> {
+    println!("{}.", err);
+    print!("Open the logbook read-only? [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
 fn main() -> Result<(), AppError> {
-    let database = RefCell::new(database::Database::from_app_database()?);
-    let app_context = AppContext::new(&database)?;
+    // Optional first argument is path to logbook file, default logbook in app data dir is used otherwise.
+    let db_path = match std::env::args_os().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => database::Database::default_path()?,
+    };
+    let database = RefCell::new(database::Database::from_path(&db_path)?);
+    let app_context = match AppContext::new(&database) {
+        Ok(app_context) => app_context,
+        Err(err @ AppError::SchemaTooNew { .. }) => {
+            if !confirm_read_only(&err)? {
+                return Err(err);
+            }
+            *database.borrow_mut() = database::Database::open_read_only(&db_path)?;
+            AppContext::new(&database)?
+        },
+        Err(err) => return Err(err),
+    };
+    // Failing to store recent list must not prevent opening the logbook.
+    let _ = database::RecentLogbooks::remember(&db_path);
 
     // setup terminal
     enable_raw_mode()?;
@@ -79,4 +79,9 @@ pub trait UIElement {
     fn set_focused(&mut self, _focused :bool) {
 
     }
+
+    /// Element shown over others which gets keyboard input before any other element, e.g. opened dialog with text input.
+    fn captures_input(&self) -> bool {
+        false
+    }
 }
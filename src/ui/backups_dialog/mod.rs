@@ -0,0 +1,156 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState}, style::{Style, Color, Modifier}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, database::backup::BackupInfo, data::qso_time};
+
+use super::{define_typed_element, AlertDialogStyle};
+
+
+#[derive(Default)]
+pub struct BackupsDialogState {
+    opened: bool,
+}
+
+#[derive(Default)]
+pub struct BackupsDialog {
+    state: BackupsDialogState,
+    table_state: TableState,
+    backups: Vec<BackupInfo>,
+}
+define_typed_element!(BackupsDialog);
+
+
+impl BackupsDialog {
+    fn reload(&mut self, app_ctx :&mut AppContext) {
+        match app_ctx.db.borrow().list_backups() {
+            Ok(backups) => self.backups = backups,
+            Err(err) => {
+                self.backups = vec!();
+                app_ctx.actions.add(Actions::ShowError(format!("Unable to list backups: {}", err)));
+            }
+        }
+        self.table_state.select((!self.backups.is_empty()).then_some(0));
+    }
+
+    fn move_selection(&mut self, step :isize) {
+        let count = self.backups.len() as isize;
+        if count == 0 {
+            self.table_state.select(None);
+            return;
+        }
+        let index = self.table_state.selected().map_or(0, |i| (i as isize + step).rem_euclid(count));
+        self.table_state.select(Some(index as usize));
+    }
+
+    fn backup_now(&mut self, app_ctx :&mut AppContext) {
+        let result = app_ctx.db.borrow().backup("manual");
+        match result {
+            Ok(path) => app_ctx.actions.add(Actions::ShowInfo(format!("Backup stored in {}", path.display()))),
+            Err(err) => app_ctx.actions.add(Actions::ShowError(format!("Backup failed: {}", err))),
+        }
+        self.reload(app_ctx);
+    }
+
+    fn restore_selected(&mut self, app_ctx :&mut AppContext) {
+        let Some(backup) = self.table_state.selected().and_then(|i| self.backups.get(i)) else {
+            return;
+        };
+
+        app_ctx.actions.add(Actions::ShowConfirm(
+            format!(
+                "Replace current logbook with backup from {} ({} QSOs)? Current state is backed up first.",
+                qso_time::format_utc(backup.created),
+                backup.qso_count.map_or("?".to_string(), |c| c.to_string())
+            ),
+            AlertDialogStyle::Warning,
+            Box::new(Actions::RestoreBackup(backup.path.clone()))
+        ));
+        self.close();
+    }
+}
+
+
+
+impl DialogInterface for BackupsDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+}
+
+impl UIElement for BackupsDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, 20.min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Backups").borders(Borders::ALL),
+            area
+        );
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ]).split(area);
+
+        let rows :Vec<Row> = self.backups.iter()
+            .map(|backup| Row::new([
+                qso_time::format_utc(backup.created),
+                backup.qso_count.map_or("?".to_string(), |c| c.to_string()),
+                backup.path.file_name().map_or(String::new(), |n| n.to_string_lossy().to_string()),
+            ]))
+            .collect();
+        f.render_stateful_widget(
+            Table::new(rows)
+                .header(Row::new(["Created (UTC)", "QSOs", "File"]).style(Style::default().bg(Color::Cyan)))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol(">> ")
+                .widths(&[
+                    Constraint::Min(20),
+                    Constraint::Min(6),
+                    Constraint::Percentage(70),
+                ]),
+            layout[0],
+            &mut self.table_state
+        );
+
+        let location = crate::database::backup::backups_dir(app_ctx.db.borrow().get_path());
+        f.render_widget(
+            Paragraph::new(format!("Enter to restore, (b)ackup now, Esc to close - {}", location.display())),
+            layout[1]
+        );
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('k') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.reload(app_ctx);
+            self.open();
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Enter => self.restore_selected(app_ctx),
+            KeyCode::Char('b') => self.backup_now(app_ctx),
+            _ => {}
+        }
+        EventResult::Handled
+    }
+}
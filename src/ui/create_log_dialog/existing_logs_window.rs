@@ -1,5 +1,5 @@
 use ratatui::{prelude::{Rect, Constraint}, widgets::{TableState, Table, Block, Borders, Row, Clear}, style::{Style, Color}};
-use crate::{ui::define_typed_element, traits::{UIElement, RenderResult}, common_types::RenderFrame, app_context::AppContext, data::position::Position};
+use crate::{ui::define_typed_element, traits::{UIElement, RenderResult}, common_types::RenderFrame, app_context::AppContext, data::{position::Position, dupes::DupeChecker, LogEntry}};
 
 
 
@@ -12,7 +12,8 @@ pub struct ExistingLogsWindow<'a> {
 define_typed_element!(ExistingLogsWindow<'_>);
 
 impl ExistingLogsWindow<'_> {
-    pub fn from_call_search(app_ctx :&mut AppContext, call_term :&str, locator_term :&str) -> Result<Self, rusqlite::Error> {
+    /// Calls are marked as duplicates when log like `candidate` made with them would be one.
+    pub fn from_call_search(app_ctx :&mut AppContext, call_term :&str, locator_term :&str, dupes :&DupeChecker, candidate :&LogEntry) -> Result<Self, rusqlite::Error> {
         if call_term.len() < 2 && locator_term.len() < 2 {
             return Ok(Self {
                 rows: vec!(),
@@ -20,11 +21,6 @@ impl ExistingLogsWindow<'_> {
             });
         }
 
-        let cur_race_id = match app_ctx.data.current_race_id {
-        Some(id) => id,
-            None => 0
-        };
-
         let db = app_ctx.db.borrow();
         let conn = db.get_connection();
         let mut stmt;
@@ -42,29 +38,26 @@ impl ExistingLogsWindow<'_> {
 
 
         let my_pos = app_ctx.data.my_position();
+        let mut same_call = candidate.clone();
         let mut final_rows = vec![];
         while let Some(row) = rows.next()? {
             let call :String = row.get(0)?;
             let locator :String = row.get(1)?;
             let count :i64 = row.get(2)?;
 
-            let pos = Position::from_qth(&locator).unwrap_or(my_pos);
-            let azimuth = pos.azimuth_to(&my_pos);
-
-            // TODO optimize original query to ned require this. Possibly using JOIN ?
-            let mut stmt = conn.prepare_cached("SELECT id FROM LogEntry where race_id=?1 AND call=?2")?;
-            let mut dupl_results = stmt.query((cur_race_id, &call))?;
-            let has_duplicites = match dupl_results.next()? {
-                Some(_) => true,
-                None => false
+            let azimuth = match (Position::from_qth(&locator), my_pos.as_ref()) {
+                (Ok(pos), Ok(my_pos)) => format!("{:.1}", pos.azimuth_to(my_pos)),
+                _ => String::new(),
             };
 
+            same_call.call = call.clone();
+            let has_duplicites = dupes.find(&same_call).is_some();
 
             let table_row = Row::new([
                 call,
                 locator,
                 format!("{}", count),
-                format!("{:.1}", azimuth),
+                azimuth,
                 (if has_duplicites { "DUP" } else { "" }).to_string()
             ]);
             final_rows.push(table_row);
@@ -8,13 +8,30 @@ pub enum InputFields {
     Call = 0,
     Code,
     QTH,
+    Freq,
+    Mode,
+    Submode,
+    RstSent,
+    RstRcvd,
+    SerialSent,
+    SerialRcvd,
+    Time,
+    Notes,
     LAST
 }
 
 
 impl Display for InputFields {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            InputFields::RstSent => write!(f, "RST sent"),
+            InputFields::RstRcvd => write!(f, "RST rcvd"),
+            InputFields::SerialSent => write!(f, "Nr sent"),
+            InputFields::SerialRcvd => write!(f, "Nr rcvd"),
+            InputFields::Time => write!(f, "Time (UTC, HHMM / -5m)"),
+            InputFields::QTH => write!(f, "QTH (locator / lat lon / UTM)"),
+            _ => write!(f, "{:?}", self)
+        }
     }
 }
 
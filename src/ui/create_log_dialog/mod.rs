@@ -1,15 +1,15 @@
 extern crate unicode_width;
 
 use crossterm::event::{KeyEvent, KeyCode};
-use ratatui::{layout::{Rect, Layout, Direction, Constraint}, widgets::{Block, Clear, Borders}};
+use ratatui::{layout::{Rect, Layout, Direction, Constraint}, widgets::{Block, Clear, Borders, Paragraph}};
 
-use crate::{data::{LogEntry, position::Position}, map_api::OnlineMap, traits::{DialogHelpers, EventResult, RenderResult, UIElement}, actions::Actions, common_types::RenderFrame, app_context::AppContext};
+use crate::{data::{LogEntry, position::Position, band, qso_time, coordinates, dxcc, scoring::{Score, ScoringRules}, dupes::DupeChecker}, map_api::OnlineMap, traits::{DialogHelpers, EventResult, RenderResult, UIElement}, actions::Actions, common_types::RenderFrame, app_context::AppContext};
 
 mod input_fields;
 use input_fields::InputFields;
 use crate::traits::DialogInterface;
 
-use super::{define_typed_element, Input};
+use super::{define_typed_element, Input, AlertDialogStyle};
 
 mod existing_logs_window;
 use existing_logs_window::ExistingLogsWindow;
@@ -31,10 +31,51 @@ impl Default for CreateLogDialogState {
 
 
 
+/// Values which are kept between consecutive logs so operator does not have to type them again.
+#[derive(Default)]
+struct CreateLogDefaults {
+    frequency: String,
+    mode: String,
+    submode: String,
+}
+
+const INPUT_COLUMNS :usize = 2;
+
+
+/// Score and duplicates of the current race, computed again only when the race, edited log or data they depend on change.
+#[derive(Default)]
+struct RaceCache {
+    source: Option<(Option<i64>, Option<i64>, [u32; 4])>,
+    rules: Option<ScoringRules>,
+    score: Score,
+    dupes: DupeChecker,
+}
+
+impl RaceCache {
+    fn refresh(&mut self, log_to_edit :Option<i64>, app_ctx :&AppContext) {
+        let data = &app_ctx.data;
+        let source = Some((
+            data.current_race_id,
+            log_to_edit,
+            [data.logs.get_version(), data.races.get_version(), data.stations.get_version(), data.configs.get_version()]
+        ));
+        if self.source == source {
+            return;
+        }
+        self.source = source;
+        self.rules = Some(data.scoring_rules(data.current_race_id));
+        self.score = data.race_score(data.current_race_id);
+        self.dupes = DupeChecker::new(data, data.current_race_id, log_to_edit);
+    }
+}
+
+
 pub struct CreateLogDialog {
     state: CreateLogDialogState,
     log_to_edit: Option<i64>,
     inputs: Vec<Input>,
+    defaults: CreateLogDefaults,
+    race: RaceCache,
 }
 define_typed_element!(CreateLogDialog);
 
@@ -46,6 +87,8 @@ impl Default for CreateLogDialog {
             state: CreateLogDialogState::default(),
             log_to_edit: None,
             inputs: vec!(),
+            defaults: CreateLogDefaults::default(),
+            race: RaceCache::default(),
         };
 
         for idx in 0..InputFields::LAST as u8 {
@@ -74,46 +117,191 @@ impl CreateLogDialog {
         self.state.opened = true;
         self.set_field(InputFields::Call, log.call.clone());
         self.set_field(InputFields::Code, log.code.clone().unwrap_or("".to_string()));
-        self.set_field(InputFields::QTH, log.position().map(|v| v.to_qth()).unwrap_or("".to_string()));
+        self.set_field(InputFields::QTH, log.position().map(|v| v.to_qth_precision(log.locator.len())).unwrap_or("".to_string()));
+        self.set_field(InputFields::Freq, log.frequency.map(band::format_frequency).or(log.band.clone()).unwrap_or_default());
+        self.set_field(InputFields::Mode, log.mode.clone().unwrap_or_default());
+        self.set_field(InputFields::Submode, log.submode.clone().unwrap_or_default());
+        self.set_field(InputFields::RstSent, log.rst_sent.clone().unwrap_or_default());
+        self.set_field(InputFields::RstRcvd, log.rst_rcvd.clone().unwrap_or_default());
+        self.set_field(InputFields::SerialSent, log.serial_sent.map_or(String::new(), |v| v.to_string()));
+        self.set_field(InputFields::SerialRcvd, log.serial_rcvd.map_or(String::new(), |v| v.to_string()));
+        self.set_field(InputFields::Time, qso_time::format_utc(log.time));
+        self.set_field(InputFields::Notes, log.notes.clone().unwrap_or_default());
 
         self.log_to_edit = Some(log.id);
     }
 
+    fn optional_field(&self, field :InputFields) -> Option<String> {
+        let value = self.get_field(field).trim().to_uppercase();
+        (!value.is_empty()).then_some(value)
+    }
+
+    fn optional_number(&self, field :InputFields) -> Result<Option<i64>, String> {
+        let value = self.get_field(field).trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        value.parse().map(Some).map_err(|_| format!("Error: '{}' is not valid number for {}", value, field))
+    }
+
+    /// Fills serial number and reports for new log so operator can send them right away.
+    fn prefill(&mut self, app_ctx :&AppContext) {
+        let report = LogEntry::default_report(self.get_field(InputFields::Mode));
+        self.set_field(InputFields::RstSent, report.to_string());
+        self.set_field(InputFields::RstRcvd, report.to_string());
+        self.set_field(InputFields::SerialSent, app_ctx.data.next_serial(app_ctx.data.current_race_id).to_string());
+    }
+
+    /// Keeps reports in sync with mode as long as operator did not change them manually.
+    fn update_reports(&mut self, previous_mode :&str) {
+        let previous_report = LogEntry::default_report(previous_mode);
+        let report = LogEntry::default_report(self.get_field(InputFields::Mode));
+
+        for field in [InputFields::RstSent, InputFields::RstRcvd] {
+            if self.get_field(field).is_empty() || self.get_field(field) == previous_report {
+                self.set_field(field, report.to_string());
+            }
+        }
+    }
+
+    /// Copies values of the form into log. Returns error message when some of the values is invalid.
+    fn fill_log(&self, log :&mut LogEntry) -> Result<(), String> {
+        log.time = qso_time::parse(self.get_field(InputFields::Time), log.time, qso_time::now())
+            .map_err(|err| format!("Error: {}", err))?;
+        log.call = self.get_field(InputFields::Call).clone();
+        log.code = Some(self.get_field(InputFields::Code).clone());
+
+        // Frequency field accepts also just band name when exact frequency is not known.
+        let frequency = self.get_field(InputFields::Freq).trim();
+        log.band = None;
+        match band::parse_frequency(frequency) {
+            Some(hz) => log.set_frequency(Some(hz)),
+            None if frequency.is_empty() => log.frequency = None,
+            None => match band::band_by_name(frequency) {
+                Some(band) => {
+                    log.frequency = None;
+                    log.band = Some(band.name.to_string());
+                },
+                None => return Err(format!("Error: '{}' is not valid frequency nor band", frequency)),
+            }
+        }
+
+        log.mode = self.optional_field(InputFields::Mode);
+        log.submode = self.optional_field(InputFields::Submode);
+
+        let report = LogEntry::default_report(log.mode.as_deref().unwrap_or_default());
+        log.rst_sent = self.optional_field(InputFields::RstSent).or(Some(report.to_string()));
+        log.rst_rcvd = self.optional_field(InputFields::RstRcvd).or(Some(report.to_string()));
+        log.serial_sent = self.optional_number(InputFields::SerialSent)?;
+        log.serial_rcvd = self.optional_number(InputFields::SerialRcvd)?;
+        // Notes are free text, unlike other fields they keep case as typed.
+        let notes = self.get_field(InputFields::Notes).trim();
+        log.notes = (!notes.is_empty()).then(|| notes.to_string());
+
+        // Coordinates are stored as locator, so the log can be exported to any format.
+        log.locator = coordinates::to_locator(self.get_field(InputFields::QTH))
+            .map_err(|err| format!("Error: {}", err))?;
+        Ok(())
+    }
+
+    /// Log with values of the form filled in as far as they are valid.
+    fn candidate_log(&self, app_ctx :&AppContext) -> LogEntry {
+        let mut log = self.log_to_edit
+            .and_then(|id| app_ctx.data.logs.get(id).cloned())
+            .unwrap_or(LogEntry {
+                race_id: app_ctx.data.current_race_id,
+                station_id: app_ctx.data.current_station_id(app_ctx.data.current_race_id),
+                ..Default::default()
+            });
+        let _ = self.fill_log(&mut log);
+        log.call = log.call.trim().to_uppercase();
+        log
+    }
+
+    /// Running score of the race with the number of multipliers, and points the QSO being entered would add.
+    fn score_line(&self, candidate :&LogEntry, app_ctx :&AppContext) -> String {
+        let Some(rules) = &self.race.rules else {
+            return String::new();
+        };
+        let score = &self.race.score;
+        let mut line = format!(" Score: {:.0} ({} QSOs, {} mults)", score.total(), score.qsos, score.multipliers());
+
+        if candidate.call.is_empty() {
+            return line;
+        }
+        if self.race.dupes.find(candidate).is_some() {
+            line += ", this QSO is DUPE, 0 points";
+        } else if self.log_to_edit.is_none() {
+            // Edited log is already counted in the score.
+            let (delta, new_multiplier) = score.delta(rules, candidate, app_ctx.data.log_distance_km(candidate));
+            line += &format!(", this QSO +{:.0}{}", delta, if new_multiplier { " new mult" } else { "" });
+        }
+        line
+    }
+
+    fn remember_defaults(&mut self) {
+        self.defaults = CreateLogDefaults {
+            frequency: self.get_field(InputFields::Freq).clone(),
+            mode: self.get_field(InputFields::Mode).clone(),
+            submode: self.get_field(InputFields::Submode).clone(),
+        };
+    }
 
-    fn save(&mut self, app_ctx :&mut AppContext) {
-
-        match self.log_to_edit.as_mut() {
-            Some(row_id) => {
-                let log = app_ctx.data.logs.get(*row_id);
-                match log.map(|v| v.clone()) {
-                    Some(mut log) => {
-                        log.call = self.get_field(InputFields::Call).clone();
-                        log.locator = self.get_field(InputFields::QTH).clone();
-                        log.code = Some(self.get_field(InputFields::Code).clone());
-                        let result = app_ctx.data.logs.edit(log.clone());
-                        if result.is_err() {
-                            app_ctx.actions.add(Actions::ShowError(format!("Error: {:?}", result.err().unwrap())));
-                        }
-                    },
-                    None => {
-                        app_ctx.actions.add(Actions::ShowError(format!("Error: Log with id {} not found", row_id)));
-                    }
+
+    /// Duplicate QSO is saved only when `allow_dupe` is set, otherwise operator is asked first.
+    /// Log already flagged as duplicate is saved without asking again.
+    fn save(&mut self, app_ctx :&mut AppContext, allow_dupe :bool) {
+        let mut log = match self.log_to_edit {
+            Some(row_id) => match app_ctx.data.logs.get(row_id).cloned() {
+                Some(log) => log,
+                None => {
+                    app_ctx.actions.add(Actions::ShowError(format!("Error: Log with id {} not found", row_id)));
+                    return;
                 }
             },
+            None => LogEntry {
+                race_id: app_ctx.data.current_race_id,
+                station_id: app_ctx.data.current_station_id(app_ctx.data.current_race_id),
+                ..Default::default()
+            },
+        };
+        if let Err(message) = self.fill_log(&mut log) {
+            app_ctx.actions.add(Actions::ShowError(message));
+            return;
+        }
+
+        let original = DupeChecker::new(&app_ctx.data, log.race_id, self.log_to_edit)
+            .find(&log)
+            .and_then(|id| app_ctx.data.logs.get(id));
+        if let Some(original) = original.filter(|_| !allow_dupe && !log.dupe) {
+            app_ctx.actions.add(Actions::ShowConfirm(
+                format!(
+                    "{} was already worked at {} UTC, log it anyway as duplicate with no points?",
+                    original.call, qso_time::format_utc(original.time)
+                ),
+                AlertDialogStyle::Warning,
+                Box::new(Actions::SaveDupeLog)
+            ));
+            return;
+        }
+        log.dupe = original.is_some();
+
+        let result = match self.log_to_edit {
+            Some(_) => app_ctx.data.logs.edit(log).map_err(|err| format!("Error: {}", err)),
             None => {
-                let res = app_ctx.data.logs.add(LogEntry{
-                    call: self.get_field(InputFields::Call).clone(),
-                    locator: self.get_field(InputFields::QTH).clone(),
-                    code: Some(self.get_field(InputFields::Code).clone()),
-                    race_id: app_ctx.data.current_race_id,
-                    ..Default::default()
-                });
-                if res.is_err() {
-                    app_ctx.actions.add(Actions::ShowError(format!("Error creating log: {:?}", res.err().unwrap())));
+                if log.serial_sent.is_none() {
+                    log.serial_sent = Some(app_ctx.data.next_serial(log.race_id));
                 }
+                app_ctx.data.logs.add(log)
+                    .map_err(|err| format!("Error creating log: {}", err))
+                    .map(|_| self.remember_defaults())
             }
+        };
+
+        match result {
+            Ok(_) => self.close(),
+            Err(message) => app_ctx.actions.add(Actions::ShowError(message)),
         }
-        self.close();
     }
 
     fn set_focus(&mut self, field :InputFields) {
@@ -134,6 +322,9 @@ impl CreateLogDialog {
         for idx in 0..InputFields::LAST as usize {
             self.inputs[idx].clear();
         }
+        self.set_field(InputFields::Freq, self.defaults.frequency.clone());
+        self.set_field(InputFields::Mode, self.defaults.mode.clone());
+        self.set_field(InputFields::Submode, self.defaults.submode.clone());
 
         self.set_focus(InputFields::Call);
     }
@@ -198,7 +389,8 @@ impl UIElement for CreateLogDialog {
             return Ok(());
         }
 
-        let mut area = DialogHelpers::center_rect_size(rect.width / 2, 13, rect);
+        let input_rows = (InputFields::LAST as usize).div_ceil(INPUT_COLUMNS);
+        let mut area = DialogHelpers::center_rect_size(rect.width / 2, input_rows as u16 * 3 + 6, rect);
         area.x += rect.width / 5;
         f.render_widget(Clear, area); //this clears out the background
         f.render_widget(
@@ -207,11 +399,13 @@ impl UIElement for CreateLogDialog {
         );
 
         let mut constraints = vec!();
-        for _ in 0..InputFields::LAST as u8 {
+        for _ in 0..input_rows {
             constraints.push(Constraint::Length(3));
         }
         constraints.push(Constraint::Length(1));
-
+        constraints.push(Constraint::Length(1));
+        constraints.push(Constraint::Length(1));
+        constraints.push(Constraint::Length(1));
 
         let popup_layout = Layout::default()
             .direction(Direction::Vertical)
@@ -219,15 +413,55 @@ impl UIElement for CreateLogDialog {
             .constraints(constraints)
             .split(area);
 
-        for (idx, input) in self.inputs.iter_mut().enumerate() {
-            input.on_draw(f, popup_layout[idx], app_ctx)?;
+        for (row, inputs) in self.inputs.chunks_mut(INPUT_COLUMNS).enumerate() {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, INPUT_COLUMNS as u32); INPUT_COLUMNS])
+                .split(popup_layout[row]);
+
+            for (column, input) in inputs.iter_mut().enumerate() {
+                input.on_draw(f, columns[column], app_ctx)?;
+            }
+        };
+
+        let frequency = self.get_field(InputFields::Freq);
+        let band_name = band::parse_frequency(frequency)
+            .and_then(band::band_for_frequency)
+            .or(band::band_by_name(frequency))
+            .map_or("-", |band| band.name);
+        // Empty time field means the log is stored with current time, or keeps its time when edited.
+        let reference = self.log_to_edit
+            .and_then(|id| app_ctx.data.logs.get(id))
+            .map_or(qso_time::now(), |log| log.time);
+        let time = qso_time::parse(self.get_field(InputFields::Time), reference, qso_time::now())
+            .map_or("invalid".to_string(), |time| format!("{} UTC", qso_time::format_utc(time)));
+        f.render_widget(Paragraph::new(format!(" Band: {}, Time: {}", band_name, time)), popup_layout[input_rows]);
+
+        let entity = dxcc::database().lookup(self.get_field(InputFields::Call))
+            .map_or(String::new(), |info| format!(" Entity: {}", info.describe()));
+        f.render_widget(Paragraph::new(entity), popup_layout[input_rows + 1]);
+
+        self.race.refresh(self.log_to_edit, app_ctx);
+        let candidate = self.candidate_log(app_ctx);
+        f.render_widget(Paragraph::new(self.score_line(&candidate, app_ctx)), popup_layout[input_rows + 2]);
+
+        let qth = self.get_field(InputFields::QTH).trim();
+        let qth_preview = match coordinates::to_locator(qth) {
+            _ if qth.is_empty() => String::new(),
+            Ok(locator) => Position::from_qth(&locator).map_or(String::new(), |position| {
+                format!(" Position: {} ({})", coordinates::format(&position), position.to_qth_precision(locator.len()))
+            }),
+            Err(err) => format!(" Position: {}", err),
         };
+        f.render_widget(Paragraph::new(qth_preview), popup_layout[input_rows + 3]);
 
 
         let logs_window = ExistingLogsWindow::from_call_search(
             app_ctx,
             self.get_field(InputFields::Call),
-            self.get_field(InputFields::QTH)
+            self.get_field(InputFields::QTH),
+            &self.race.dupes,
+            &candidate
         );
         if logs_window.is_ok() {
             logs_window.unwrap().on_draw(f, Rect {
@@ -252,11 +486,18 @@ impl UIElement for CreateLogDialog {
             KeyCode::Esc => self.close(),
             KeyCode::Tab => self.set_focus(self.state.current_input.next()),
             KeyCode::BackTab => self.set_focus(self.state.current_input.prev()),
-            KeyCode::Enter => self.save(app_ctx),
+            KeyCode::Enter => self.save(app_ctx, false),
             KeyCode::PageDown => self.find_location(&self.get_field(InputFields::Call).clone(), app_ctx),
-            KeyCode::F(2) => self.clear_form(),
+            KeyCode::F(2) => {
+                self.clear_form();
+                self.prefill(app_ctx);
+            },
             _ => {
+                let previous_mode = self.get_field(InputFields::Mode).clone();
                 self.get_focused().on_input(key, app_ctx);
+                if self.state.current_input == InputFields::Mode {
+                    self.update_reports(&previous_mode);
+                }
             }
         };
         EventResult::Handled
@@ -270,8 +511,15 @@ impl UIElement for CreateLogDialog {
                 }
                 EventResult::Handled
             },
+            Actions::SaveDupeLog => {
+                if self.is_opened() {
+                    self.save(app_ctx, true);
+                }
+                EventResult::Handled
+            },
             Actions::CreateLogWanted => {
                 self.open();
+                self.prefill(app_ctx);
                 EventResult::Handled
             },
 
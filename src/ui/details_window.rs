@@ -1,5 +1,6 @@
-use ratatui::{Frame, widgets::{Block, Borders, Clear, Paragraph}, layout::Rect, text::Span};
-use crate::{traits::{UIElement, RenderResult, EventResult}, data::LogEntry, actions::Actions, common_types::RenderFrame, app_context::AppContext};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{Frame, widgets::{Block, Borders, Clear, Paragraph, Wrap}, layout::Rect, text::{Span, Line}, style::{Style, Color}};
+use crate::{traits::{UIElement, RenderResult, EventResult, DialogInterface}, data::{History, qso_time, dxcc}, actions::Actions, common_types::RenderFrame, app_context::AppContext};
 
 use super::unique_ids::define_typed_element;
 
@@ -7,7 +8,12 @@ use super::unique_ids::define_typed_element;
 
 #[derive(Default)]
 struct DetailsWindowState {
-    selected_log :Option<LogEntry>,
+    opened :bool,
+    selected_log :Option<i64>,
+    history :Vec<History>,
+    // Log id and data version history was loaded for.
+    history_source :Option<(i64, u32)>,
+    scroll :u16,
 }
 
 #[derive(Default)]
@@ -17,8 +23,9 @@ pub struct DetailsWindow {
 define_typed_element!(DetailsWindow);
 
 impl DetailsWindow {
-    pub fn set_log(&mut self, log :LogEntry) {
-        self.state.selected_log = Some(log);
+    pub fn set_log(&mut self, log_id :Option<i64>) {
+        self.state.selected_log = log_id;
+        self.state.scroll = 0;
     }
 
     fn render_info<B: ratatui::backend::Backend>(&self, f :&mut Frame<B>, label :&str, text :&String, rect :&mut Rect) -> () {
@@ -34,6 +41,51 @@ impl DetailsWindow {
 
         rect.y += 1;
     }
+
+    fn reload_history(&mut self, log_id :i64, app_ctx :&mut AppContext) {
+        let source = Some((log_id, app_ctx.data.logs.get_version()));
+        if self.state.history_source == source {
+            return;
+        }
+        self.state.history_source = source;
+
+        match app_ctx.data.logs.history(log_id) {
+            Ok(history) => self.state.history = history,
+            Err(err) => {
+                self.state.history = vec!();
+                app_ctx.actions.add(Actions::ShowError(format!("Unable to load history of log: {}", err)));
+            }
+        }
+    }
+
+    fn render_history(&self, f :&mut RenderFrame, rect :Rect) {
+        // Newest revision first, that is what is usually looked for.
+        let lines :Vec<Line> = self.state.history.iter().rev()
+            .map(|revision| Line::from(vec!(
+                Span::styled(format!("{} ", qso_time::format_utc(revision.time)), Style::default().fg(Color::Yellow)),
+                Span::raw(revision.summary()),
+            )))
+            .collect();
+
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().title(format!("History ({} revisions)", self.state.history.len())).borders(Borders::TOP))
+                .wrap(Wrap { trim: false })
+                .scroll((self.state.scroll, 0)),
+            rect
+        );
+    }
+}
+
+
+impl DialogInterface for DetailsWindow {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
 }
 
 
@@ -41,54 +93,126 @@ impl UIElement for DetailsWindow {
     implement_typed_element!();
 
     fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
-        if self.state.selected_log.is_none() {
+        if ! self.is_opened() {
             return Ok(());
         }
 
-        f.render_widget(Clear, rect);
+        // Right half of the screen so log table stays visible and selection can be moved.
+        let area = Rect {
+            x: rect.x + rect.width / 2,
+            y: rect.y,
+            width: rect.width - rect.width / 2,
+            height: rect.height,
+        };
+        f.render_widget(Clear, area);
         f.render_widget(
             Block::default()
-                .title("Details")
+                .title("Details - (i) to close, PageUp/PageDown to scroll history")
                 .borders(Borders::ALL),
-            rect
+            area
         );
+        if area.width < 20 || area.height < 10 {
+            return Ok(());
+        }
 
         let mut rect = Rect {
-            x: rect.x + 3,
-            y: rect.y + 2,
-            width: rect.width - 5,
+            x: area.x + 3,
+            y: area.y + 2,
+            width: area.width - 5,
             height: 1,
         };
 
-        let log = self.state.selected_log.as_ref().unwrap();
-        self.render_info(f, "Name:", &log.call, &mut rect);
+        let Some(log) = self.state.selected_log.and_then(|id| app_ctx.data.logs.get(id)).cloned() else {
+            self.render_info(f, "", &"No log selected".to_string(), &mut rect);
+            return Ok(());
+        };
+
+        self.render_info(f, "Call:", &log.call, &mut rect);
+        let entity = dxcc::database().lookup(&log.call)
+            .map_or("Unknown".to_string(), |info| format!("{}, near {}", info.describe(), info.position.to_qth_precision(4)));
+        self.render_info(f, "Entity:", &entity, &mut rect);
+        self.render_info(f, "Time:", &format!("{} UTC", qso_time::format_utc(log.time)), &mut rect);
+        self.render_info(f, "Band:", &log.band.clone().unwrap_or_default(), &mut rect);
         self.render_info(f, "Locator:", &log.locator, &mut rect);
 
         match log.position() {
             Some(pos) => {
-                let self_pos = &app_ctx.data.config.own_position;
-                self.render_info(f, "QTH: ", &pos.to_qth(), &mut rect);
-                self.render_info(f, "Distance: ", &format!("{:.2} km", self_pos.distance_to(&pos).km()), &mut rect);
+                let config = app_ctx.data.config();
+                let uncertainty = log.distance_uncertainty(app_ctx)
+                    .map_or(String::new(), |value| format!(" ± {}", config.format_distance(&value)));
+                let (short_path, long_path) = match log.my_position(app_ctx) {
+                    Ok(self_pos) => (
+                        format!(
+                            "{}{}, azimuth {:.1}°",
+                            config.format_distance(&config.distance(&self_pos, &pos)), uncertainty, self_pos.azimuth_to(&pos)
+                        ),
+                        format!(
+                            "{}, azimuth {:.1}°",
+                            config.format_distance(&config.long_path_distance(&self_pos, &pos)), self_pos.long_path_azimuth_to(&pos)
+                        ),
+                    ),
+                    Err(_) => ("Unknown".to_string(), "Unknown".to_string()),
+                };
+                self.render_info(f, "QTH: ", &pos.to_qth_precision(log.locator.len()), &mut rect);
+                self.render_info(f, "Short path: ", &short_path, &mut rect);
+                self.render_info(f, "Long path: ", &long_path, &mut rect);
             },
             None => {
                 self.render_info(f, "QTH: ", &"Unknown".to_string(), &mut rect);
-                self.render_info(f, "Distance: ", &"Unknown".to_string(), &mut rect);
+                self.render_info(f, "Short path: ", &"Unknown".to_string(), &mut rect);
+                self.render_info(f, "Long path: ", &"Unknown".to_string(), &mut rect);
             },
         }
 
+        self.reload_history(log.id, app_ctx);
+        let history_rect = Rect {
+            x: rect.x,
+            y: rect.y + 1,
+            width: rect.width,
+            height: (area.y + area.height).saturating_sub(rect.y + 2),
+        };
+        self.render_history(f, history_rect);
         Ok(())
     }
 
-    fn on_action(&mut self, action :&Actions, app_ctx :&mut AppContext) -> crate::traits::EventResult {
+    fn on_input(&mut self, key :&KeyEvent, _app_ctx :&mut AppContext) -> EventResult {
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        // Other keys are left for the log table so selection can be moved while details are shown.
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('i') => {
+                self.close();
+                EventResult::Handled
+            },
+            KeyCode::PageDown => {
+                self.state.scroll = self.state.scroll.saturating_add(5);
+                EventResult::Handled
+            },
+            KeyCode::PageUp => {
+                self.state.scroll = self.state.scroll.saturating_sub(5);
+                EventResult::Handled
+            },
+            _ => EventResult::NotHandled
+        }
+    }
+
+    fn on_action(&mut self, action :&Actions, _app_ctx :&mut AppContext) -> crate::traits::EventResult {
         match action {
-            Actions::FocusLog(log_id) => {
-                match log_id {
-                    Some(log_id) => self.set_log(app_ctx.data.logs.get(*log_id).unwrap().clone()),
-                    None => self.set_log(Default::default())
+            Actions::ToggleDetails => {
+                if self.is_opened() {
+                    self.close();
+                } else {
+                    self.open();
                 }
+                EventResult::Handled
+            },
+            Actions::FocusLog(log_id) => {
+                self.set_log(*log_id);
                 EventResult::NotHandled
             }
             _ => EventResult::NOOP
         }
     }
-}
\ No newline at end of file
+}
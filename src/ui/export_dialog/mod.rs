@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use platform_dirs::UserDirs;
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, formats::{ExportFormat, ExportField}};
+
+use super::{define_typed_element, Input};
+
+
+const FIELD_COLUMNS :usize = 3;
+
+
+#[derive(Default)]
+pub struct ExportDialogState {
+    opened: bool,
+    race_id: Option<i64>,
+    format: Option<ExportFormat>,
+    focused_input: usize,
+}
+
+pub struct ExportDialog {
+    state: ExportDialogState,
+    path_input: Input,
+
+    fields: Vec<ExportField>,
+    field_inputs: Vec<Input>,
+}
+define_typed_element!(ExportDialog);
+
+
+impl Default for ExportDialog {
+    fn default() -> Self {
+        let mut path_input = Input::default().set_label("File".to_string());
+        path_input.set_focused(true);
+
+        Self {
+            state: ExportDialogState::default(),
+            path_input,
+            fields: vec!(),
+            field_inputs: vec!(),
+        }
+    }
+}
+
+
+impl ExportDialog {
+    fn default_path(app_ctx :&AppContext) -> String {
+        let mut path = UserDirs::new().map_or(PathBuf::from("."), |dirs| dirs.document_dir);
+
+        let scope = app_ctx.data.current_race_id
+            .and_then(|id| app_ctx.data.races.get(id))
+            .map_or("all".to_string(), |race| race.name.replace(|c :char| !c.is_alphanumeric(), "_"));
+        let date = chrono::Utc::now().format("%Y%m%d");
+
+        path.push(format!("radiolog_{}_{}.adi", scope, date));
+        path.to_string_lossy().to_string()
+    }
+
+    fn open_for(&mut self, app_ctx :&AppContext) {
+        self.state.race_id = app_ctx.data.current_race_id;
+        self.path_input.set(ExportDialog::default_path(app_ctx));
+        self.refresh_format(app_ctx);
+        self.open();
+    }
+
+    /// Rebuilds format specific fields whenever file extension switches to different format.
+    fn refresh_format(&mut self, app_ctx :&AppContext) {
+        let format = ExportFormat::from_path(&PathBuf::from(self.path_input.get()));
+        if format == self.state.format {
+            return;
+        }
+
+        self.state.format = format;
+        self.fields = format.map_or(vec!(), |f| f.fields(&app_ctx.data, self.state.race_id));
+        self.field_inputs = self.fields.iter()
+            .map(|field| {
+                let mut input = Input::default().set_label(field.label.to_string());
+                input.set(field.value.clone());
+                input
+            })
+            .collect();
+    }
+
+    fn set_focus(&mut self, index :usize) {
+        self.get_focused().set_focused(false);
+        self.state.focused_input = index % (self.field_inputs.len() + 1);
+        self.get_focused().set_focused(true);
+    }
+
+    fn get_focused(&mut self) -> &mut Input {
+        match self.state.focused_input {
+            0 => &mut self.path_input,
+            index => &mut self.field_inputs[index - 1],
+        }
+    }
+
+    fn export(&mut self, app_ctx :&mut AppContext) {
+        let path = PathBuf::from(self.path_input.get());
+        let Some(format) = self.state.format else {
+            app_ctx.actions.add(Actions::ShowError(format!(
+                "Unknown export format, use one of: {}", ExportFormat::supported_extensions()
+            )));
+            return;
+        };
+
+        for (field, input) in self.fields.iter_mut().zip(self.field_inputs.iter()) {
+            field.value = input.get().trim().to_string();
+        }
+
+        match format.export(&app_ctx.data, self.state.race_id, &self.fields, &path) {
+            Ok(count) => {
+                app_ctx.actions.add(Actions::ShowInfo(format!("Exported {} logs into {}", count, path.display())));
+                self.close();
+            },
+            Err(err) => {
+                app_ctx.actions.add(Actions::ShowError(format!("Export failed: {}", err)));
+            }
+        }
+    }
+
+    fn scope_name(&self, app_ctx :&AppContext) -> String {
+        self.state.race_id
+            .and_then(|id| app_ctx.data.races.get(id))
+            .map_or("all logs".to_string(), |race| format!("race '{}'", race.name))
+    }
+}
+
+
+
+impl DialogInterface for ExportDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+
+    fn close(&mut self) {
+        self.set_focus(0);
+        self.state.format = None;
+        self.set_opened(false);
+    }
+}
+
+impl UIElement for ExportDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let field_rows = self.field_inputs.len().div_ceil(FIELD_COLUMNS) as u16;
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, (7 + field_rows * 3).min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title(format!("Export {}", self.scope_name(app_ctx))).borders(Borders::ALL),
+            area
+        );
+
+        let mut constraints = vec!(Constraint::Length(3));
+        constraints.extend((0..field_rows).map(|_| Constraint::Length(3)));
+        constraints.push(Constraint::Length(1));
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.path_input.on_draw(f, layout[0], app_ctx)?;
+        for (row, inputs) in self.field_inputs.chunks_mut(FIELD_COLUMNS).enumerate() {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, FIELD_COLUMNS as u32); FIELD_COLUMNS])
+                .split(layout[row + 1]);
+
+            for (column, input) in inputs.iter_mut().enumerate() {
+                input.on_draw(f, columns[column], app_ctx)?;
+            }
+        }
+
+        f.render_widget(
+            Paragraph::new(format!("Enter to export ({}), Tab to move, Esc to close", ExportFormat::supported_extensions())),
+            layout[layout.len() - 1]
+        );
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('e') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open_for(app_ctx);
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => self.export(app_ctx),
+            KeyCode::Tab => self.set_focus(self.state.focused_input + 1),
+            KeyCode::BackTab => self.set_focus(self.state.focused_input + self.field_inputs.len()),
+            _ => {
+                self.get_focused().on_input(key, app_ctx);
+                if self.state.focused_input == 0 {
+                    self.refresh_format(app_ctx);
+                }
+            }
+        }
+        EventResult::Handled
+    }
+}
@@ -0,0 +1,107 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph, Wrap}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, data::log_filter::{LogFilter, FILTER_HELP}};
+
+use super::{define_typed_element, Input};
+
+
+#[derive(Default)]
+pub struct FilterDialogState {
+    opened: bool,
+}
+
+pub struct FilterDialog {
+    state: FilterDialogState,
+    input: Input,
+}
+define_typed_element!(FilterDialog);
+
+
+impl Default for FilterDialog {
+    fn default() -> Self {
+        let mut input = Input::default().set_label("Filter".to_string());
+        input.set_focused(true);
+
+        Self {
+            state: FilterDialogState::default(),
+            input,
+        }
+    }
+}
+
+
+impl FilterDialog {
+    fn apply(&mut self, app_ctx :&mut AppContext) {
+        let expression = self.input.get().trim().to_string();
+        match LogFilter::parse(&expression) {
+            Ok(_) => {
+                app_ctx.actions.add(Actions::SetLogFilter(expression));
+                self.close();
+            },
+            Err(err) => app_ctx.actions.add(Actions::ShowError(err.to_string())),
+        }
+    }
+}
+
+
+
+impl DialogInterface for FilterDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+}
+
+impl UIElement for FilterDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let area = DialogHelpers::center_rect_size((rect.width / 3) * 2, 9.min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Filter logs").borders(Borders::ALL),
+            area
+        );
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(2),
+                Constraint::Length(1),
+            ]).split(area);
+
+        self.input.on_draw(f, layout[0], app_ctx)?;
+        f.render_widget(Paragraph::new(FILTER_HELP).wrap(Wrap { trim: true }), layout[1]);
+        f.render_widget(Paragraph::new("Enter to apply, empty filter shows all logs, Esc to close"), layout[2]);
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('f') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open();
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => self.apply(app_ctx),
+            _ => {
+                self.input.on_input(key, app_ctx);
+            }
+        }
+        EventResult::Handled
+    }
+}
@@ -0,0 +1,192 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph, Table, Row, Wrap}, style::{Style, Color}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, formats::ImportPreview, data::qso_time};
+
+use super::{define_typed_element, Input};
+
+
+#[derive(Default)]
+pub struct ImportDialogState {
+    opened: bool,
+    preview: Option<ImportPreview>,
+}
+
+pub struct ImportDialog {
+    state: ImportDialogState,
+    path_input: Input,
+}
+define_typed_element!(ImportDialog);
+
+
+impl Default for ImportDialog {
+    fn default() -> Self {
+        let mut path_input = Input::default().set_label("File (.adi, .adx)".to_string());
+        path_input.set_focused(true);
+
+        Self {
+            state: ImportDialogState::default(),
+            path_input,
+        }
+    }
+}
+
+
+impl ImportDialog {
+    fn load_preview(&mut self, app_ctx :&mut AppContext) {
+        let path = PathBuf::from(self.path_input.get());
+        match ImportPreview::from_file(&path, &app_ctx.data, app_ctx.data.current_race_id) {
+            Ok(preview) => self.state.preview = Some(preview),
+            Err(err) => app_ctx.actions.add(Actions::ShowError(format!("Unable to read {}: {}", path.display(), err))),
+        }
+    }
+
+    fn commit(&mut self, app_ctx :&mut AppContext) {
+        let Some(preview) = self.state.preview.take() else {
+            return;
+        };
+
+        match preview.commit(&mut app_ctx.data) {
+            Ok(count) => {
+                app_ctx.actions.add(Actions::ShowInfo(format!("Imported {} logs", count)));
+                self.close();
+            },
+            Err(err) => {
+                app_ctx.actions.add(Actions::ShowError(format!("Import failed, nothing was stored: {}", err)));
+            }
+        }
+    }
+
+    fn render_preview(&self, f :&mut RenderFrame, area :Rect, preview :&ImportPreview, app_ctx :&AppContext) -> RenderResult {
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(4),
+                Constraint::Length(1),
+            ]).split(area);
+
+        let target = preview.race_id
+            .and_then(|id| app_ctx.data.races.get(id))
+            .map_or("no race".to_string(), |race| format!("race '{}'", race.name));
+        f.render_widget(Paragraph::new(format!(
+            "File: {}\nTarget: {}\nRecords: {}, new: {}, duplicates: {}, invalid: {}",
+            preview.path.display(),
+            target,
+            preview.total(),
+            preview.new_logs.len(),
+            preview.duplicates.len(),
+            preview.errors.len()
+        )), layout[0]);
+
+        let rows :Vec<Row> = preview.new_logs.iter()
+            .take(layout[1].height as usize)
+            .map(|log| Row::new([
+                qso_time::format_utc(log.time),
+                log.call.clone(),
+                log.locator.clone(),
+                log.code.clone().unwrap_or_default(),
+            ]))
+            .collect();
+        f.render_widget(
+            Table::new(rows)
+                .header(Row::new(["Time (UTC)", "Call", "QTH", "Code"]).style(Style::default().bg(Color::Cyan)))
+                .widths(&[
+                    Constraint::Min(20),
+                    Constraint::Percentage(30),
+                    Constraint::Min(7),
+                    Constraint::Min(9),
+                ]),
+            layout[1]
+        );
+
+        f.render_widget(
+            Paragraph::new(preview.errors.join("\n"))
+                .style(Style::default().fg(Color::LightRed))
+                .wrap(Wrap { trim: true }),
+            layout[2]
+        );
+        f.render_widget(
+            Paragraph::new(format!("Enter to import {} new logs, Esc to cancel", preview.new_logs.len())),
+            layout[3]
+        );
+        Ok(())
+    }
+}
+
+
+
+impl DialogInterface for ImportDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+
+    fn close(&mut self) {
+        self.state.preview = None;
+        self.set_opened(false);
+    }
+}
+
+impl UIElement for ImportDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let height = if self.state.preview.is_some() { 25 } else { 7 };
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, height.min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Import logs").borders(Borders::ALL),
+            area
+        );
+
+        if let Some(preview) = self.state.preview.as_ref() {
+            return self.render_preview(f, area, preview, app_ctx);
+        }
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ]).split(area);
+
+        self.path_input.on_draw(f, layout[0], app_ctx)?;
+        f.render_widget(Paragraph::new("Enter to preview import, Esc to close"), layout[1]);
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('l') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open();
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc if self.state.preview.is_some() => self.state.preview = None,
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter if self.state.preview.is_some() => self.commit(app_ctx),
+            KeyCode::Enter => self.load_preview(app_ctx),
+            _ if self.state.preview.is_none() => {
+                self.path_input.on_input(key, app_ctx);
+            },
+            _ => {}
+        }
+        EventResult::Handled
+    }
+}
@@ -128,7 +128,7 @@ impl<'a> UIElement for LogList<'a> {
         match action {
             Actions::FocusLog(log_id) => {
                 match log_id {
-                    Some(id) => self.state.select(app_ctx.data.logs.find_index_of(*id)),
+                    Some(id) => self.state.select(app_ctx.data.logs.index_of(*id).ok()),
                     None => self.state.select(None),
                 }
                 EventResult::NotHandled
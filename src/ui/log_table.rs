@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{widgets::{TableState, Table, Block, Borders, Row}, prelude::Rect, style::{Style, Modifier, Color}};
-use crate::{traits::{UIElement, RenderResult, EventResult}, app_context::AppContext, common_types::RenderFrame, actions::Actions, data::LogEntry};
+use crate::{traits::{UIElement, RenderResult, EventResult}, app_context::AppContext, common_types::RenderFrame, actions::Actions, data::{LogEntry, DistanceCache, log_filter::{LogFilter, LogSort, SortColumn}}};
 
 use super::{define_typed_element, AlertDialogStyle};
 
@@ -8,51 +10,87 @@ use super::{define_typed_element, AlertDialogStyle};
 
 #[derive(Default)]
 pub struct LogTable {
-    state :TableState,
     border_style: Style,
+    local_time: bool,
+
+    filter: LogFilter,
+    sort: Option<LogSort>,
+
+    // Ids of logs in the order they are shown and their positions, only visible part is rendered.
+    view: Vec<i64>,
+    view_index: HashMap<i64, usize>,
+    // Race view was built for, view is rebuilt only when it, data, filter or sorting changes.
+    view_race: Option<Option<i64>>,
+    view_dirty: bool,
+    race_log_count: usize,
+    distances: DistanceCache,
+
+    selected: Option<i64>,
+    // Index of first visible row and number of visible rows in last render.
+    offset: usize,
+    page_height: usize,
 }
 define_typed_element!(LogTable);
 
 
 impl LogTable {
-    fn next(&self, app_ctx :&mut AppContext) -> i64 {
-        let current_race_id = app_ctx.data.current_race_id;
-        let log_count = app_ctx.data.race_logs(current_race_id).count();
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= log_count - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            },
+    fn selected_index(&self) -> Option<usize> {
+        self.selected.and_then(|id| self.view_index.get(&id).copied())
+    }
+
+    /// Id of log moved by `step` rows from the selected one, wraps around when stepping by single row.
+    fn step_selection(&self, step :isize) -> Option<i64> {
+        let count = self.view.len() as isize;
+        if count == 0 {
+            return None;
+        }
+        let index = match self.selected_index() {
+            None if step < 0 => count - 1,
             None => 0,
+            Some(i) if step.abs() == 1 => (i as isize + step).rem_euclid(count),
+            Some(i) => (i as isize + step).clamp(0, count - 1),
         };
-        app_ctx.data.logs.get_by_index(i).unwrap().id
+        self.view.get(index as usize).copied()
     }
 
-    fn previous(&self, app_ctx :&mut AppContext) -> i64 {
+    fn update_view(&mut self, app_ctx :&AppContext) {
+        let data_changed = self.distances.refresh(app_ctx);
         let current_race_id = app_ctx.data.current_race_id;
-        let log_count = app_ctx.data.race_logs(current_race_id).count();
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    log_count - 1
-                } else {
-                    i - 1
-                }
-            },
-            None => 0,
+        if !data_changed && !self.view_dirty && self.view_race == Some(current_race_id) {
+            return;
+        }
+        self.view_race = Some(current_race_id);
+        self.view_dirty = false;
+
+        self.race_log_count = 0;
+        let mut view = Vec::new();
+        for log in app_ctx.data.race_logs(current_race_id) {
+            self.race_log_count += 1;
+            if self.filter.matches(log, &self.distances) {
+                view.push(log.id);
+            }
+        }
+        if let Some(sort) = self.sort {
+            sort.sort(&mut view, app_ctx, &self.distances);
+        }
+
+        self.view_index = view.iter().enumerate().map(|(index, id)| (*id, index)).collect();
+        self.view = view;
+    }
+
+    /// Pressing key of already sorted column reverses the order.
+    fn toggle_sort(&mut self, column :SortColumn) {
+        self.sort = match self.sort {
+            Some(sort) if sort.column == column => Some(LogSort { column, ascending: !sort.ascending }),
+            _ => Some(LogSort { column, ascending: true }),
         };
-        app_ctx.data.logs.get_by_index(i).unwrap().id
+        self.view_dirty = true;
     }
 
-    fn selected_log(&self, app_ctx :&mut AppContext) -> Option<i64> {
-        if let Some(i) = self.state.selected() {
-            let current_race_id = app_ctx.data.current_race_id;
-            return app_ctx.data.race_logs(current_race_id).nth(i).map_or(None, |item| Some(item.id))
+    fn focus(&mut self, log_id :Option<i64>, app_ctx :&mut AppContext) {
+        if let Some(log_id) = log_id {
+            app_ctx.actions.add(Actions::FocusLog(Some(log_id)));
         }
-        None
     }
 }
 
@@ -64,33 +102,53 @@ impl UIElement for LogTable {
         let selected_style = Style::default().add_modifier(Modifier::REVERSED);
         let normal_style = Style::default().bg(Color::Blue);
 
-        let header = LogEntry::table_header()
+        let header = LogEntry::table_header(self.local_time, &app_ctx.data.config().units, self.sort)
             .style(normal_style)
             .height(1);
 
-        let current_race_id = app_ctx.data.current_race_id;
-        let rows :Vec<Row> = app_ctx.data.race_logs(current_race_id)
-            .map(|item| item.table_row(app_ctx)).collect();
-
-        if let Some(sel_index) = self.state.selected() {
-            if rows.len() == 0 {
-                self.state.select(None);
-            } else if sel_index >= rows.len() {
-                self.state.select(Some(rows.len() - 1));
+        self.update_view(app_ctx);
+
+        // Borders and header take 3 lines, only rows fitting the rest are built.
+        self.page_height = (rect.height as usize).saturating_sub(3).max(1);
+        let selected_index = self.selected_index();
+        if let Some(index) = selected_index {
+            if index < self.offset {
+                self.offset = index;
+            } else if index >= self.offset + self.page_height {
+                self.offset = index + 1 - self.page_height;
             }
         }
+        self.offset = self.offset.min(self.view.len().saturating_sub(self.page_height));
 
+        let visible_end = (self.offset + self.page_height).min(self.view.len());
+        let rows :Vec<Row> = self.view[self.offset..visible_end].iter()
+            .filter_map(|id| app_ctx.data.logs.get(*id))
+            .map(|item| item.table_row(self.local_time, &self.distances, app_ctx.data.config())).collect();
+
+        let mut state = TableState::default();
+        state.select(selected_index.map(|index| index - self.offset));
+
+        let widths = LogEntry::table_column_constraints(self.local_time);
+        let filter = match self.filter.is_empty() {
+            true => String::new(),
+            false => format!(" filter '{}' ({} of {})", self.filter.expression(), self.view.len(), self.race_log_count),
+        };
+        let logbook = {
+            let db = app_ctx.db.borrow();
+            let name = db.get_path().file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+            if db.is_read_only() { format!("{}, read-only", name) } else { name }
+        };
         let t = Table::new(rows)
             .header(header)
             .block(
                 Block::default()
                     .border_style(self.border_style)
-                    .borders(Borders::ALL).title("Logs - (a)dd, CTRL+(r)aces, toggle (m)ap")
+                    .borders(Borders::ALL).title(format!("Logs [{}]{} - (a)dd, CTRL+(r)aces, CTRL+s(t)ations, CTRL+(e)xport, CTRL+(l)oad, CTRL+(o)ptions, CTRL+log(b)ooks, CTRL+bac(k)ups, toggle (m)ap, (i)nfo, (s)tats, local (t)ime, CTRL+(f)ilter, sort 1-5, (/) search", logbook, filter))
             )
             .highlight_style(selected_style)
             .highlight_symbol(">> ")
-            .widths(LogEntry::table_column_constraints());
-        f.render_stateful_widget(t, rect, &mut self.state);
+            .widths(&widths);
+        f.render_stateful_widget(t, rect, &mut state);
         Ok(())
     }
 
@@ -98,13 +156,27 @@ impl UIElement for LogTable {
     fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
         match key.code {
             KeyCode::Up => {
-                let new_id = self.previous(app_ctx);
-                app_ctx.actions.add(Actions::FocusLog(Some(new_id)));
+                self.focus(self.step_selection(-1), app_ctx);
                 EventResult::Handled
             },
             KeyCode::Down => {
-                let new_id = self.next(app_ctx);
-                app_ctx.actions.add(Actions::FocusLog(Some(new_id)));
+                self.focus(self.step_selection(1), app_ctx);
+                EventResult::Handled
+            },
+            KeyCode::PageUp => {
+                self.focus(self.step_selection(-(self.page_height as isize)), app_ctx);
+                EventResult::Handled
+            },
+            KeyCode::PageDown => {
+                self.focus(self.step_selection(self.page_height as isize), app_ctx);
+                EventResult::Handled
+            },
+            KeyCode::Home => {
+                self.focus(self.view.first().copied(), app_ctx);
+                EventResult::Handled
+            },
+            KeyCode::End => {
+                self.focus(self.view.last().copied(), app_ctx);
                 EventResult::Handled
             },
             KeyCode::Left => {
@@ -113,14 +185,14 @@ impl UIElement for LogTable {
             },
 
             KeyCode::Enter => {
-                if let Some(log_id) = self.selected_log(app_ctx) {
+                if let Some(log_id) = self.selected {
                     app_ctx.actions.add(Actions::EditLog(log_id));
                 }
                 EventResult::Handled
             },
 
             KeyCode::Delete => {
-                let to_del = self.selected_log(app_ctx);
+                let to_del = self.selected.filter(|id| app_ctx.data.logs.get(*id).is_some());
                 if to_del.is_none() {
                     return EventResult::NotHandled;
                 }
@@ -139,6 +211,28 @@ impl UIElement for LogTable {
                 EventResult::Handled
             },
 
+            KeyCode::Char('i') => {
+                app_ctx.actions.add(Actions::ToggleDetails);
+                EventResult::Handled
+            },
+
+            KeyCode::Char('s') => {
+                app_ctx.actions.add(Actions::ToggleStats);
+                EventResult::Handled
+            },
+
+            KeyCode::Char('t') => {
+                self.local_time = !self.local_time;
+                EventResult::Handled
+            },
+
+            KeyCode::Char(key) if SortColumn::from_key(key).is_some() => {
+                if let Some(column) = SortColumn::from_key(key) {
+                    self.toggle_sort(column);
+                }
+                EventResult::Handled
+            },
+
             _ => EventResult::NotHandled
         }
     }
@@ -146,12 +240,25 @@ impl UIElement for LogTable {
     fn on_action(&mut self, action :&Actions, app_ctx :&mut AppContext) -> EventResult {
         match action {
             Actions::FocusLog(log_id) => {
-                match log_id {
-                    Some(id) => self.state.select(app_ctx.data.logs.find_index_of(*id)),
-                    None => self.state.select(None),
+                self.selected = *log_id;
+
+                // Log would not be visible, filter is dropped so focused log can be seen.
+                let hidden = log_id.and_then(|id| app_ctx.data.logs.get(id))
+                    .is_some_and(|log| !self.filter.matches(log, &self.distances));
+                if hidden {
+                    self.filter = LogFilter::default();
+                    self.view_dirty = true;
                 }
                 EventResult::NotHandled
             },
+            Actions::SetLogFilter(expression) => {
+                match LogFilter::parse(expression) {
+                    Ok(filter) => self.filter = filter,
+                    Err(err) => app_ctx.actions.add(Actions::ShowError(err.to_string())),
+                }
+                self.view_dirty = true;
+                EventResult::Handled
+            },
             Actions::DeleteLog(log_id) => {
                 let res = app_ctx.data.logs.remove(*log_id);
                 if res.is_err() {
@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph}, style::{Style, Color, Modifier}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, database::RecentLogbooks};
+
+use super::{define_typed_element, Input};
+
+
+#[derive(Default)]
+pub struct LogbooksDialogState {
+    opened: bool,
+    path_focused: bool,
+}
+
+pub struct LogbooksDialog {
+    state: LogbooksDialogState,
+    recent: RecentLogbooks,
+    list_state: ListState,
+    path_input: Input,
+}
+define_typed_element!(LogbooksDialog);
+
+
+impl Default for LogbooksDialog {
+    fn default() -> Self {
+        Self {
+            state: LogbooksDialogState::default(),
+            recent: RecentLogbooks::default(),
+            list_state: ListState::default(),
+            path_input: Input::default().set_label("Open or create logbook file".to_string()),
+        }
+    }
+}
+
+
+impl LogbooksDialog {
+    fn set_path_focused(&mut self, focused :bool) {
+        self.state.path_focused = focused;
+        self.path_input.set_focused(focused);
+    }
+
+    fn move_selection(&mut self, step :isize) {
+        let count = self.recent.paths.len() as isize;
+        if count == 0 {
+            self.list_state.select(None);
+            return;
+        }
+        let index = self.list_state.selected().map_or(0, |i| (i as isize + step).rem_euclid(count));
+        self.list_state.select(Some(index as usize));
+    }
+
+    fn selected_path(&self) -> Option<PathBuf> {
+        self.list_state.selected().and_then(|i| self.recent.paths.get(i)).cloned()
+    }
+
+    fn open_path(&mut self, path :PathBuf, app_ctx :&mut AppContext) {
+        app_ctx.actions.add(Actions::OpenDatabase(path));
+        self.close();
+    }
+
+    fn forget_selected(&mut self, app_ctx :&mut AppContext) {
+        if let Some(path) = self.selected_path() {
+            self.recent.remove(&path);
+            if let Err(err) = self.recent.save() {
+                app_ctx.actions.add(Actions::ShowError(format!("Unable to update recent logbooks: {}", err)));
+            }
+            self.move_selection(0);
+        }
+    }
+}
+
+
+
+impl DialogInterface for LogbooksDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+
+    fn open(&mut self) {
+        self.recent = RecentLogbooks::load();
+        self.list_state.select((!self.recent.paths.is_empty()).then_some(0));
+        self.path_input.clear();
+        self.set_path_focused(self.recent.paths.is_empty());
+        self.set_opened(true);
+    }
+}
+
+impl UIElement for LogbooksDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, 20.min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Logbooks").borders(Borders::ALL),
+            area
+        );
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(3),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ]).split(area);
+
+        let current = app_ctx.db.borrow().get_path().to_path_buf();
+        f.render_widget(Paragraph::new(format!("Current: {}", current.display())), layout[0]);
+
+        let items :Vec<ListItem> = self.recent.paths.iter()
+            .map(|path| {
+                let marker = if path.as_path() == current.as_path() { "* " } else { "  " };
+                ListItem::new(format!("{}{}", marker, path.display()))
+            })
+            .collect();
+        let list_style = if self.state.path_focused { Style::default() } else { Style::default().fg(Color::Yellow) };
+        f.render_stateful_widget(
+            List::new(items)
+                .block(Block::default().title("Recent - Enter to open, delete to forget").borders(Borders::ALL).style(list_style))
+                .highlight_style(Style::default().bg(Color::LightBlue).add_modifier(Modifier::BOLD))
+                .highlight_symbol(">> "),
+            layout[1],
+            &mut self.list_state
+        );
+
+        self.path_input.on_draw(f, layout[2], app_ctx)?;
+        f.render_widget(Paragraph::new("Tab to switch between list and file, Esc to close"), layout[3]);
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('b') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open();
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab | KeyCode::BackTab => self.set_path_focused(!self.state.path_focused),
+            KeyCode::Enter if self.state.path_focused => {
+                let path = self.path_input.get().trim().to_string();
+                if !path.is_empty() {
+                    self.open_path(PathBuf::from(path), app_ctx);
+                }
+            },
+            KeyCode::Enter => {
+                if let Some(path) = self.selected_path() {
+                    self.open_path(path, app_ctx);
+                }
+            },
+            _ if self.state.path_focused => {
+                self.path_input.on_input(key, app_ctx);
+            },
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Delete => self.forget_selected(app_ctx),
+            _ => {}
+        }
+        EventResult::Handled
+    }
+}
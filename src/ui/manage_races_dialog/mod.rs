@@ -1,7 +1,7 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph}};
 
-use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult, UIEvents}, common_types::RenderFrame, app_context::AppContext, actions::Actions, ui_handler::{UIHandler, UIElementID}, data::Race};
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult, UIEvents}, common_types::RenderFrame, app_context::AppContext, actions::Actions, ui_handler::{UIHandler, UIElementID}, data::{Race, scoring::{ScoringRules, SCORING_HELP}, dupes::{DupeRule, DUPE_RULE_HELP}}};
 
 use super::{define_typed_element, RacesList, Input};
 
@@ -21,6 +21,9 @@ pub struct ManageRacesDialog {
     race_name_inp: UIElementID,
     race_my_loc_inp: UIElementID,
     race_my_call_inp: UIElementID,
+    race_scoring_inp: UIElementID,
+    race_dupe_rule_inp: UIElementID,
+    race_station_inp: UIElementID,
 }
 define_typed_element!(ManageRacesDialog);
 
@@ -32,6 +35,9 @@ impl Default for ManageRacesDialog {
         let race_name_inp = handler.add(Box::new(Input::default().set_label("Race name".to_string())));
         let race_my_loc_inp = handler.add(Box::new(Input::default().set_label("My location".to_string())));
         let race_my_call_inp = handler.add(Box::new(Input::default().set_label("My call".to_string())));
+        let race_scoring_inp = handler.add(Box::new(Input::default().set_label(format!("Scoring ({})", SCORING_HELP))));
+        let race_dupe_rule_inp = handler.add(Box::new(Input::default().set_label(format!("Dupes ({})", DUPE_RULE_HELP))));
+        let race_station_inp = handler.add(Box::new(Input::default().set_label("Station (call or #id, empty for active one)".to_string())));
 
         Self {
             state: ManageRacesDialogState::default(),
@@ -40,7 +46,10 @@ impl Default for ManageRacesDialog {
             race_list,
             race_name_inp,
             race_my_loc_inp,
-            race_my_call_inp
+            race_my_call_inp,
+            race_scoring_inp,
+            race_dupe_rule_inp,
+            race_station_inp
         }
     }
 }
@@ -53,6 +62,19 @@ impl ManageRacesDialog {
             app_ctx.actions.add(Actions::ShowError("You have to provide race name with at least 3 characters".to_string()));
             return;
         }
+        let scoring = self.get_val(self.race_scoring_inp).trim().to_lowercase();
+        let dupe_rule = self.get_val(self.race_dupe_rule_inp).trim().to_lowercase();
+        if let Err(err) = ScoringRules::parse(&scoring).and_then(|_| DupeRule::parse(&dupe_rule)) {
+            app_ctx.actions.add(Actions::ShowError(err.to_string()));
+            return;
+        }
+        let station_id = match self.find_station(app_ctx) {
+            Ok(station_id) => station_id,
+            Err(err) => {
+                app_ctx.actions.add(Actions::ShowError(err));
+                return;
+            }
+        };
 
         let res;
         if let Some(race_id) = self.state.edited_race_id {
@@ -61,6 +83,9 @@ impl ManageRacesDialog {
                     race.my_call = self.get_val(self.race_my_call_inp);
                     race.my_location = self.get_val(self.race_my_loc_inp);
                     race.name = self.get_val(self.race_name_inp);
+                    race.scoring = scoring;
+                    race.dupe_rule = dupe_rule;
+                    race.station_id = station_id;
                     res = app_ctx.data.races.edit(race);
 
                     self.state.edited_race_id = None;
@@ -75,12 +100,15 @@ impl ManageRacesDialog {
                 name: race_name,
                 my_location: self.get_val(self.race_my_loc_inp),
                 my_call: self.get_val(self.race_my_call_inp),
+                station_id,
+                scoring,
+                dupe_rule,
                 ..Default::default()
             });
         }
 
         if res.is_err() {
-            app_ctx.actions.add(Actions::ShowError(format!("Error managing race: {:?}", res.err().unwrap())));
+            app_ctx.actions.add(Actions::ShowError(format!("Error managing race: {}", res.err().unwrap())));
         }
     }
 
@@ -89,7 +117,10 @@ impl ManageRacesDialog {
         [
             &self.race_name_inp,
             &self.race_my_loc_inp,
-            &self.race_my_call_inp
+            &self.race_my_call_inp,
+            &self.race_scoring_inp,
+            &self.race_dupe_rule_inp,
+            &self.race_station_inp
         ].iter().for_each(|id| {
             self.handler.get::<Input>(id).expect("Invalid UI state").clear();
         });
@@ -111,6 +142,39 @@ impl ManageRacesDialog {
             self.set_val(self.race_name_inp, race.name.clone());
             self.set_val(self.race_my_loc_inp, race.my_location.clone());
             self.set_val(self.race_my_call_inp, race.my_call.clone());
+            self.set_val(self.race_scoring_inp, race.scoring.clone());
+            self.set_val(self.race_dupe_rule_inp, race.dupe_rule.clone());
+
+            let station = race.station_id.and_then(|id| app_ctx.data.stations.get(id));
+            if let Some(station) = station {
+                let same_call = app_ctx.data.stations.iter().filter(|s| s.call.eq_ignore_ascii_case(&station.call)).count();
+                let value = if same_call == 1 { station.call.clone() } else { format!("#{}", station.id) };
+                self.set_val(self.race_station_inp, value);
+            }
+        }
+    }
+
+    /// Station profile typed by its call, or by `#id` when more profiles share the call.
+    fn find_station(&mut self, app_ctx :&AppContext) -> Result<Option<i64>, String> {
+        let value = self.get_val(self.race_station_inp).trim().to_string();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        if let Some(id) = value.strip_prefix('#') {
+            return id.parse().ok()
+                .filter(|id| app_ctx.data.stations.get(*id).is_some())
+                .map(Some)
+                .ok_or(format!("There is no station profile {}", value));
+        }
+
+        let matching :Vec<i64> = app_ctx.data.stations.iter()
+            .filter(|station| station.call.eq_ignore_ascii_case(&value))
+            .map(|station| station.id)
+            .collect();
+        match matching[..] {
+            [id] => Ok(Some(id)),
+            [] => Err(format!("There is no station profile with call {}", value)),
+            _ => Err(format!("More station profiles have call {}, use #id of one of them", value)),
         }
     }
 }
@@ -144,7 +208,7 @@ impl UIElement for ManageRacesDialog {
             return Ok(());
         }
 
-        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, 15, rect);
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, 24, rect);
         f.render_widget(Clear, area); //this clears out the background
         f.render_widget(
             Block::default().title("Manage races").borders(Borders::ALL),
@@ -162,6 +226,9 @@ impl UIElement for ManageRacesDialog {
         let inputs_layout = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Length(3),
@@ -175,13 +242,16 @@ impl UIElement for ManageRacesDialog {
             .constraints([
                 Constraint::Percentage(40),
                 Constraint::Min(1),
-            ]).split(inputs_layout[3]);
+            ]).split(inputs_layout[6]);
         f.render_widget(Paragraph::new("CTRL+S to save"), help_layout[1]);
 
         self.handler.draw_single(&self.race_list, self.frame_index, f, layout[0], app_ctx)?;
         self.handler.draw_single(&self.race_name_inp, self.frame_index, f, inputs_layout[0], app_ctx)?;
         self.handler.draw_single(&self.race_my_loc_inp, self.frame_index, f, inputs_layout[1], app_ctx)?;
         self.handler.draw_single(&self.race_my_call_inp, self.frame_index, f, inputs_layout[2], app_ctx)?;
+        self.handler.draw_single(&self.race_scoring_inp, self.frame_index, f, inputs_layout[3], app_ctx)?;
+        self.handler.draw_single(&self.race_dupe_rule_inp, self.frame_index, f, inputs_layout[4], app_ctx)?;
+        self.handler.draw_single(&self.race_station_inp, self.frame_index, f, inputs_layout[5], app_ctx)?;
         self.handler.draw_all(self.frame_index, f, app_ctx)?;
 
         Ok(())
@@ -0,0 +1,286 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph}, style::{Style, Color, Modifier}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, data::{StationProfile, position::Position}};
+
+use super::{define_typed_element, Input, AlertDialogStyle};
+
+
+#[derive(Clone, Copy)]
+enum StationField {
+    Call = 0,
+    Locator,
+    Antenna,
+    Power,
+    Description,
+}
+
+/// Input labels in the order of `StationField`.
+const LABELS :[&str; 5] = ["Call", "Locator", "Antenna", "Power (W)", "Description"];
+
+
+#[derive(Default)]
+pub struct ManageStationsDialogState {
+    opened: bool,
+    edited_station_id: Option<i64>,
+    // 0 is station list, inputs follow.
+    focused: usize,
+}
+
+pub struct ManageStationsDialog {
+    state: ManageStationsDialogState,
+    list_state: ListState,
+    inputs: Vec<Input>,
+}
+define_typed_element!(ManageStationsDialog);
+
+
+impl Default for ManageStationsDialog {
+    fn default() -> Self {
+        Self {
+            state: ManageStationsDialogState::default(),
+            list_state: ListState::default(),
+            inputs: LABELS.iter().map(|label| Input::default().set_label(label.to_string())).collect(),
+        }
+    }
+}
+
+
+impl ManageStationsDialog {
+    fn get_val(&self, field :StationField) -> String {
+        self.inputs[field as usize].get().trim().to_string()
+    }
+
+    fn set_val(&mut self, field :StationField, value :String) {
+        self.inputs[field as usize].set(value);
+    }
+
+    fn set_focus(&mut self, index :usize) {
+        if self.state.focused > 0 {
+            self.inputs[self.state.focused - 1].set_focused(false);
+        }
+        self.state.focused = index % (self.inputs.len() + 1);
+        if self.state.focused > 0 {
+            self.inputs[self.state.focused - 1].set_focused(true);
+        }
+    }
+
+    fn clear_inputs(&mut self) {
+        self.state.edited_station_id = None;
+        self.inputs.iter_mut().for_each(|input| input.clear());
+    }
+
+    fn selected_station(&self, app_ctx :&AppContext) -> Option<i64> {
+        self.list_state.selected()
+            .and_then(|i| app_ctx.data.stations.get_by_index(i))
+            .map(|station| station.id)
+    }
+
+    fn move_selection(&mut self, step :isize, app_ctx :&AppContext) {
+        let count = app_ctx.data.stations.len() as isize;
+        if count == 0 {
+            self.list_state.select(None);
+            return;
+        }
+        let index = self.list_state.selected().map_or(0, |i| (i as isize + step).rem_euclid(count));
+        self.list_state.select(Some(index as usize));
+    }
+
+    fn edit_station(&mut self, id :i64, app_ctx :&AppContext) {
+        self.clear_inputs();
+
+        if let Some(station) = app_ctx.data.stations.get(id).cloned() {
+            self.state.edited_station_id = Some(id);
+            self.set_val(StationField::Call, station.call);
+            self.set_val(StationField::Locator, station.locator);
+            self.set_val(StationField::Antenna, station.antenna);
+            self.set_val(StationField::Power, station.power);
+            self.set_val(StationField::Description, station.description);
+            self.set_focus(1);
+        }
+    }
+
+    fn save(&mut self, app_ctx :&mut AppContext) {
+        let call = self.get_val(StationField::Call).to_uppercase();
+        if call.is_empty() {
+            app_ctx.actions.add(Actions::ShowError("You have to provide call of the station".to_string()));
+            return;
+        }
+
+        let locator = self.get_val(StationField::Locator).to_uppercase();
+        if let Some(Err(err)) = (!locator.is_empty()).then(|| Position::parse_qth(&locator)) {
+            app_ctx.actions.add(Actions::ShowError(format!("'{}' is not valid QTH locator: {}", locator, err)));
+            return;
+        }
+
+        let station = StationProfile {
+            call,
+            locator,
+            antenna: self.get_val(StationField::Antenna),
+            power: self.get_val(StationField::Power),
+            description: self.get_val(StationField::Description),
+            ..Default::default()
+        };
+
+        let res = match self.state.edited_station_id.and_then(|id| app_ctx.data.stations.get(id)) {
+            Some(edited) => {
+                let station = StationProfile { id: edited.id, active: edited.active, ..station };
+                app_ctx.data.stations.edit(station)
+            },
+            // First station profile becomes active right away.
+            None => {
+                let active = app_ctx.data.active_station().is_none();
+                app_ctx.data.stations.add(StationProfile { active, ..station })
+            }
+        };
+
+        match res {
+            Ok(_) => {
+                self.clear_inputs();
+                self.set_focus(0);
+            },
+            Err(err) => app_ctx.actions.add(Actions::ShowError(format!("Error managing station: {}", err))),
+        }
+    }
+
+    fn on_list_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) {
+        match key.code {
+            KeyCode::Up => self.move_selection(-1, app_ctx),
+            KeyCode::Down => self.move_selection(1, app_ctx),
+            KeyCode::Char('a') => {
+                if let Some(id) = self.selected_station(app_ctx) {
+                    if let Err(err) = app_ctx.data.activate_station(id) {
+                        app_ctx.actions.add(Actions::ShowError(format!("Unable to activate station: {}", err)));
+                    }
+                }
+            },
+            KeyCode::Char('e') => {
+                if let Some(id) = self.selected_station(app_ctx) {
+                    self.edit_station(id, app_ctx);
+                }
+            },
+            KeyCode::Char('n') => {
+                self.clear_inputs();
+                self.set_focus(1);
+            },
+            KeyCode::Delete => {
+                if let Some(id) = self.selected_station(app_ctx) {
+                    app_ctx.actions.add(Actions::ShowConfirm(
+                        "Do you really want to delete selected station?".to_string(),
+                        AlertDialogStyle::Warning,
+                        Box::new(Actions::DeleteStation(id))
+                    ));
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+
+
+impl DialogInterface for ManageStationsDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+
+    fn open(&mut self) {
+        self.clear_inputs();
+        self.set_focus(0);
+        self.set_opened(true);
+    }
+
+    fn close(&mut self) {
+        self.set_opened(false);
+    }
+}
+
+impl UIElement for ManageStationsDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, (self.inputs.len() as u16 * 3 + 5).min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Station profiles").borders(Borders::ALL),
+            area
+        );
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(50),
+                Constraint::Min(1),
+            ]).split(area);
+
+        let mut constraints :Vec<Constraint> = self.inputs.iter().map(|_| Constraint::Length(3)).collect();
+        constraints.push(Constraint::Length(1));
+        let inputs_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(layout[1]);
+
+        let items :Vec<ListItem> = app_ctx.data.stations.iter().map(|station| station.clone().into()).collect();
+        let list_style = if self.state.focused == 0 { Style::default().fg(Color::Yellow) } else { Style::default() };
+        f.render_stateful_widget(
+            List::new(items)
+                .block(Block::default().title("(a)ctivate, (e)dit, (n)ew, delete").borders(Borders::ALL).style(list_style))
+                .highlight_style(Style::default().bg(Color::LightBlue).add_modifier(Modifier::BOLD))
+                .highlight_symbol(">> "),
+            layout[0],
+            &mut self.list_state
+        );
+
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            input.on_draw(f, inputs_layout[index], app_ctx)?;
+        }
+        f.render_widget(Paragraph::new("CTRL+S to save, Tab to move"), inputs_layout[inputs_layout.len() - 1]);
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('t') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open();
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Tab => self.set_focus(self.state.focused + 1),
+            KeyCode::BackTab => self.set_focus(self.state.focused + self.inputs.len()),
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => self.save(app_ctx),
+            _ if self.state.focused == 0 => self.on_list_input(key, app_ctx),
+            _ => {
+                self.inputs[self.state.focused - 1].on_input(key, app_ctx);
+            }
+        }
+        EventResult::Handled
+    }
+
+    fn on_action(&mut self, action :&Actions, app_ctx :&mut AppContext) -> EventResult {
+        match action {
+            Actions::DeleteStation(station_id) => {
+                if let Err(err) = app_ctx.data.stations.remove(*station_id) {
+                    app_ctx.actions.add(Actions::ShowError(format!("Error deleting station: {}", err)));
+                }
+                if self.state.edited_station_id == Some(*station_id) {
+                    self.clear_inputs();
+                }
+                EventResult::Handled
+            },
+            _ => EventResult::NotHandled
+        }
+    }
+}
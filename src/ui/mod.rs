@@ -15,12 +15,40 @@ pub use create_log_dialog::CreateLogDialog;
 mod manage_races_dialog;
 pub use manage_races_dialog::ManageRacesDialog;
 
+mod manage_stations_dialog;
+pub use manage_stations_dialog::ManageStationsDialog;
+
+mod export_dialog;
+pub use export_dialog::ExportDialog;
+
+mod stats_dialog;
+pub use stats_dialog::StatsDialog;
+
+mod search_dialog;
+pub use search_dialog::SearchDialog;
+
+mod filter_dialog;
+pub use filter_dialog::FilterDialog;
+
+mod backups_dialog;
+pub use backups_dialog::BackupsDialog;
+
+mod logbooks_dialog;
+pub use logbooks_dialog::LogbooksDialog;
+
+mod settings_dialog;
+pub use settings_dialog::SettingsDialog;
+
+mod import_dialog;
+pub use import_dialog::ImportDialog;
+
 mod alert_dialog;
 pub use alert_dialog::AlertDialog;
 pub use alert_dialog::AlertDialogButton;
 pub use alert_dialog::AlertDialogStyle;
 
 mod details_window;
+pub use details_window::DetailsWindow;
 
 mod input;
 pub use input::Input;
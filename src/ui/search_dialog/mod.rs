@@ -0,0 +1,195 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState}, style::{Style, Color, Modifier}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, data::{qso_time, search::{self, SearchHit}}};
+
+use super::{define_typed_element, Input};
+
+
+const MAX_HITS :usize = 100;
+
+
+#[derive(Default)]
+pub struct SearchDialogState {
+    opened: bool,
+    // Query hits were found for, search runs again only when it changes.
+    last_query: Option<String>,
+}
+
+pub struct SearchDialog {
+    state: SearchDialogState,
+    input: Input,
+    hits: Vec<SearchHit>,
+    table_state: TableState,
+}
+define_typed_element!(SearchDialog);
+
+
+impl Default for SearchDialog {
+    fn default() -> Self {
+        let mut input = Input::default().set_label("Search call, locator, code or notes".to_string());
+        input.set_focused(true);
+
+        Self {
+            state: SearchDialogState::default(),
+            input,
+            hits: vec!(),
+            table_state: TableState::default(),
+        }
+    }
+}
+
+
+impl SearchDialog {
+    fn update_hits(&mut self, app_ctx :&mut AppContext) {
+        let query = self.input.get().trim().to_string();
+        if self.state.last_query.as_ref() == Some(&query) {
+            return;
+        }
+
+        let result = search::search(app_ctx.db.borrow().get_connection(), &query, MAX_HITS);
+        match result {
+            Ok(hits) => self.hits = hits,
+            Err(err) => {
+                self.hits = vec!();
+                app_ctx.actions.add(Actions::ShowError(format!("Search failed: {}", err)));
+            }
+        }
+        self.state.last_query = Some(query);
+        self.table_state.select((!self.hits.is_empty()).then_some(0));
+    }
+
+    fn move_selection(&mut self, step :isize) {
+        let count = self.hits.len() as isize;
+        if count == 0 {
+            self.table_state.select(None);
+            return;
+        }
+        let index = self.table_state.selected().map_or(0, |i| (i as isize + step).rem_euclid(count));
+        self.table_state.select(Some(index as usize));
+    }
+
+    fn jump_to_selected(&mut self, app_ctx :&mut AppContext) {
+        if let Some(hit) = self.table_state.selected().and_then(|i| self.hits.get(i)) {
+            // Log table shows only logs of the current race, switch to the race of the found log.
+            // Logs without race are visible only when no race is set.
+            let race_id = app_ctx.data.logs.get(hit.log_id).and_then(|log| log.race_id);
+            if app_ctx.data.current_race_id.is_some() && app_ctx.data.current_race_id != race_id {
+                app_ctx.data.current_race_id = race_id;
+            }
+            app_ctx.actions.add(Actions::FocusLog(Some(hit.log_id)));
+            self.close();
+        }
+    }
+}
+
+
+
+impl DialogInterface for SearchDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+
+    fn open(&mut self) {
+        // Logs could change since last search.
+        self.state.last_query = None;
+        self.set_opened(true);
+    }
+}
+
+impl UIElement for SearchDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+        self.update_hits(app_ctx);
+
+        let area = DialogHelpers::center_rect_size((rect.width / 4) * 3, (rect.height / 4) * 3, rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Search logs").borders(Borders::ALL),
+            area
+        );
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ]).split(area);
+
+        self.input.on_draw(f, layout[0], app_ctx)?;
+
+        let rows :Vec<Row> = self.hits.iter()
+            .filter_map(|hit| app_ctx.data.logs.get(hit.log_id).map(|log| (hit, log)))
+            .map(|(hit, log)| {
+                let race = log.race_id
+                    .and_then(|id| app_ctx.data.races.get(id))
+                    .map_or(String::new(), |race| race.name.clone());
+                Row::new([
+                    if hit.fuzzy { "~".to_string() } else { String::new() },
+                    log.call.clone(),
+                    log.locator.clone(),
+                    race,
+                    qso_time::format_utc(log.time),
+                    log.notes.clone().unwrap_or_default(),
+                ])
+            })
+            .collect();
+        f.render_stateful_widget(
+            Table::new(rows)
+                .header(Row::new(["", "Call", "QTH", "Race", "Time (UTC)", "Notes"]).style(Style::default().bg(Color::Cyan)))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol(">> ")
+                .widths(&[
+                    Constraint::Length(1),
+                    Constraint::Min(12),
+                    Constraint::Min(8),
+                    Constraint::Min(15),
+                    Constraint::Min(20),
+                    Constraint::Percentage(40),
+                ]),
+            layout[1],
+            &mut self.table_state
+        );
+
+        f.render_widget(
+            Paragraph::new("Enter to jump to log, Up/Down to select, ~ marks similar match, Esc to close"),
+            layout[2]
+        );
+        Ok(())
+    }
+
+    fn captures_input(&self) -> bool {
+        self.is_opened()
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if ! self.is_opened() {
+            if key.code == KeyCode::Char('/') && !key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.open();
+                return EventResult::Handled;
+            }
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Enter => self.jump_to_selected(app_ctx),
+            _ => {
+                self.input.on_input(key, app_ctx);
+            }
+        }
+        EventResult::Handled
+    }
+}
@@ -0,0 +1,193 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph}};
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, DialogHelpers, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, data::{ConfigData, MAX_DISTANCE_PRECISION, position::{Position, DistanceFormula, DistanceUnit}}};
+
+use super::{define_typed_element, Input};
+
+
+#[derive(PartialEq, Clone, Copy)]
+enum SettingsField {
+    OwnCall = 0,
+    OwnLocator,
+    Name,
+    DefaultPower,
+    Units,
+    DistanceFormula,
+    DistancePrecision,
+}
+
+/// Input labels in the order of `SettingsField`.
+const LABELS :[&str; 7] = [
+    "Own call", "Own locator", "Name", "Default power (W)", "Distance units (km, mi, nmi, m)", "Distance formula (sphere, wgs84)", "Distance decimal places"
+];
+
+
+#[derive(Default)]
+pub struct SettingsDialogState {
+    opened: bool,
+    focused_input: usize,
+}
+
+pub struct SettingsDialog {
+    state: SettingsDialogState,
+    inputs: Vec<Input>,
+}
+define_typed_element!(SettingsDialog);
+
+
+impl Default for SettingsDialog {
+    fn default() -> Self {
+        let mut inputs :Vec<Input> = LABELS.iter()
+            .map(|label| Input::default().set_label(label.to_string()))
+            .collect();
+        inputs[0].set_focused(true);
+
+        Self {
+            state: SettingsDialogState::default(),
+            inputs,
+        }
+    }
+}
+
+
+impl SettingsDialog {
+    fn get_val(&self, field :SettingsField) -> String {
+        self.inputs[field as usize].get().trim().to_string()
+    }
+
+    fn open_with(&mut self, config :&ConfigData) {
+        self.inputs[SettingsField::OwnCall as usize].set(config.own_call.clone());
+        self.inputs[SettingsField::OwnLocator as usize].set(config.own_locator.clone());
+        self.inputs[SettingsField::Name as usize].set(config.name.clone());
+        self.inputs[SettingsField::DefaultPower as usize].set(config.default_power.clone());
+        self.inputs[SettingsField::Units as usize].set(config.units.clone());
+        self.inputs[SettingsField::DistanceFormula as usize].set(config.distance_formula.clone());
+        self.inputs[SettingsField::DistancePrecision as usize].set(config.distance_precision.to_string());
+        self.open();
+    }
+
+    fn set_focus(&mut self, index :usize) {
+        self.inputs[self.state.focused_input].set_focused(false);
+        self.state.focused_input = index % self.inputs.len();
+        self.inputs[self.state.focused_input].set_focused(true);
+    }
+
+    fn save(&mut self, app_ctx :&mut AppContext) {
+        let own_locator = self.get_val(SettingsField::OwnLocator).to_uppercase();
+        if let Some(Err(err)) = (!own_locator.is_empty()).then(|| Position::parse_qth(&own_locator)) {
+            app_ctx.actions.add(Actions::ShowError(format!("'{}' is not valid QTH locator: {}", own_locator, err)));
+            return;
+        }
+
+        let units = self.get_val(SettingsField::Units).to_lowercase();
+        if DistanceUnit::from_symbol(&units).is_none() {
+            let symbols :Vec<&str> = DistanceUnit::ALL.iter().map(|unit| unit.symbol()).collect();
+            app_ctx.actions.add(Actions::ShowError(format!("Unknown distance unit '{}', use one of: {}", units, symbols.join(", "))));
+            return;
+        }
+
+        let distance_precision = self.get_val(SettingsField::DistancePrecision);
+        let Some(distance_precision) = distance_precision.parse().ok().filter(|p| (0..=MAX_DISTANCE_PRECISION).contains(p)) else {
+            app_ctx.actions.add(Actions::ShowError(format!("Distance decimal places '{}' have to be number from 0 to {}", distance_precision, MAX_DISTANCE_PRECISION)));
+            return;
+        };
+
+        let distance_formula = self.get_val(SettingsField::DistanceFormula).to_lowercase();
+        if DistanceFormula::from_name(&distance_formula).is_none() {
+            app_ctx.actions.add(Actions::ShowError(format!("Unknown distance formula '{}', use one of: {}", distance_formula, DistanceFormula::NAMES.join(", "))));
+            return;
+        }
+
+        let default_power = self.get_val(SettingsField::DefaultPower);
+        if !default_power.is_empty() && default_power.parse::<f64>().is_err() {
+            app_ctx.actions.add(Actions::ShowError(format!("Default power '{}' has to be number of watts", default_power)));
+            return;
+        }
+
+        let config = ConfigData {
+            own_call: self.get_val(SettingsField::OwnCall).to_uppercase(),
+            own_locator,
+            name: self.get_val(SettingsField::Name),
+            default_power,
+            units,
+            distance_formula,
+            distance_precision,
+            ..app_ctx.data.config().clone()
+        };
+        match app_ctx.data.set_config(config) {
+            Ok(_) => self.close(),
+            Err(err) => app_ctx.actions.add(Actions::ShowError(format!("Failed to save settings: {}", err))),
+        }
+    }
+}
+
+
+
+impl DialogInterface for SettingsDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+
+    fn close(&mut self) {
+        self.set_focus(0);
+        self.set_opened(false);
+    }
+}
+
+impl UIElement for SettingsDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+
+        let area = DialogHelpers::center_rect_size(rect.width / 2, (self.inputs.len() as u16 * 3 + 3).min(rect.height), rect);
+        f.render_widget(Clear, area); //this clears out the background
+        f.render_widget(
+            Block::default().title("Settings").borders(Borders::ALL),
+            area
+        );
+
+        let mut constraints :Vec<Constraint> = self.inputs.iter().map(|_| Constraint::Length(3)).collect();
+        constraints.push(Constraint::Length(1));
+
+        let layout = Layout::default()
+            .margin(1)
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            input.on_draw(f, layout[index], app_ctx)?;
+        }
+        f.render_widget(Paragraph::new("Enter to save, Tab to move, Esc to close"), layout[layout.len() - 1]);
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if key.code == KeyCode::Char('o') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open_with(app_ctx.data.config());
+            return EventResult::Handled;
+        }
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => self.save(app_ctx),
+            KeyCode::Tab => self.set_focus(self.state.focused_input + 1),
+            KeyCode::BackTab => self.set_focus(self.state.focused_input + self.inputs.len() - 1),
+            _ => {
+                self.inputs[self.state.focused_input].on_input(key, app_ctx);
+            }
+        }
+        EventResult::Handled
+    }
+}
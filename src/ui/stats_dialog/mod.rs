@@ -0,0 +1,187 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::{Constraint, Direction, Layout, Rect}, widgets::{Block, Borders, Clear, Paragraph, BarChart}, style::{Style, Color}};
+use chrono::NaiveDateTime;
+
+use crate::{traits::{DialogInterface, UIElement, RenderResult, EventResult}, common_types::RenderFrame, app_context::AppContext, actions::Actions, data::{DistanceCache, stats::{LogStats, AZIMUTH_SECTORS}}};
+
+use super::define_typed_element;
+
+
+#[derive(Default)]
+pub struct StatsDialogState {
+    opened: bool,
+    // Race statistics are shown for, None for all logs.
+    race_id: Option<i64>,
+    // Race stats were computed for, they are computed again only when it or logs change.
+    computed_for: Option<Option<i64>>,
+}
+
+#[derive(Default)]
+pub struct StatsDialog {
+    state: StatsDialogState,
+    distances: DistanceCache,
+    stats: LogStats,
+}
+define_typed_element!(StatsDialog);
+
+
+impl StatsDialog {
+    fn update_stats(&mut self, app_ctx :&AppContext) {
+        let data_changed = self.distances.refresh(app_ctx);
+        if !data_changed && self.state.computed_for == Some(self.state.race_id) {
+            return;
+        }
+        self.state.computed_for = Some(self.state.race_id);
+        self.stats = LogStats::compute(app_ctx.data.race_logs(self.state.race_id), &self.distances);
+    }
+
+    /// Cycles between all logs and every race.
+    fn switch_race(&mut self, step :isize, app_ctx :&AppContext) {
+        let mut scopes :Vec<Option<i64>> = vec!(None);
+        scopes.extend(app_ctx.data.races.iter().map(|race| Some(race.id)));
+
+        let current = scopes.iter().position(|scope| *scope == self.state.race_id).unwrap_or(0) as isize;
+        let next = (current + step).rem_euclid(scopes.len() as isize) as usize;
+        self.state.race_id = scopes[next];
+    }
+
+    fn summary(&self, app_ctx :&AppContext) -> Vec<String> {
+        let config = app_ctx.data.config();
+        let stats = &self.stats;
+        let odx = stats.odx
+            .and_then(|(log_id, distance)| app_ctx.data.logs.get(log_id).map(|log| (log, distance)))
+            .map_or("-".to_string(), |(log, distance)| format!("{} ({} {})", config.format_distance(&distance), log.call, log.locator));
+
+        vec!(
+            format!("QSOs:            {}", stats.qso_count),
+            format!("Unique calls:    {}", stats.unique_calls),
+            format!("Unique squares:  {}", stats.unique_squares),
+            format!("ODX:             {}", odx),
+            format!("Average:         {}", stats.average_distance.map_or("-".to_string(), |avg| config.format_distance(&avg))),
+            format!("Active hours:    {}", stats.per_hour.len()),
+            format!("Best hour:       {}", stats.per_hour.values().max().map_or("-".to_string(), |max| format!("{} QSOs", max))),
+        )
+    }
+
+    fn render_per_hour(&self, f :&mut RenderFrame, rect :Rect) {
+        // Every bar takes 3 columns and gap 1, borders 2.
+        let hours = self.stats.last_hours((rect.width.saturating_sub(2) / 4) as usize);
+        let labels :Vec<String> = hours.iter()
+            .map(|(hour, _)| NaiveDateTime::from_timestamp_opt(*hour, 0).map_or("?".to_string(), |t| t.format("%H").to_string()))
+            .collect();
+        let data :Vec<(&str, u64)> = labels.iter().zip(hours.iter()).map(|(label, (_, count))| (label.as_str(), *count)).collect();
+
+        f.render_widget(
+            BarChart::default()
+                .block(Block::default().title("QSOs per hour (UTC)").borders(Borders::ALL))
+                .data(data.as_slice())
+                .bar_width(3)
+                .bar_gap(1)
+                .bar_style(Style::default().fg(Color::Yellow))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+            rect
+        );
+    }
+
+    fn render_azimuths(&self, f :&mut RenderFrame, rect :Rect) {
+        let data :Vec<(&str, u64)> = AZIMUTH_SECTORS.iter().zip(self.stats.azimuth_sectors.iter())
+            .map(|(sector, count)| (*sector, *count))
+            .collect();
+        let bar_width = (rect.width.saturating_sub(2) / AZIMUTH_SECTORS.len() as u16).saturating_sub(1).max(1);
+
+        f.render_widget(
+            BarChart::default()
+                .block(Block::default().title("Azimuth distribution").borders(Borders::ALL))
+                .data(data.as_slice())
+                .bar_width(bar_width)
+                .bar_gap(1)
+                .bar_style(Style::default().fg(Color::Cyan))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Cyan)),
+            rect
+        );
+    }
+}
+
+
+
+impl DialogInterface for StatsDialog {
+    fn set_opened(&mut self, opened :bool) {
+        self.state.opened = opened;
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state.opened
+    }
+}
+
+impl UIElement for StatsDialog {
+    implement_typed_element!();
+
+    fn render(&mut self, f :&mut RenderFrame, rect :Rect, app_ctx :&mut AppContext) -> RenderResult {
+        if ! self.is_opened() {
+            return Ok(());
+        }
+        self.update_stats(app_ctx);
+
+        let scope = self.state.race_id
+            .and_then(|id| app_ctx.data.races.get(id))
+            .map_or("all logs".to_string(), |race| format!("race {}", race.name));
+        f.render_widget(Clear, rect); //this clears out the background
+        f.render_widget(
+            Block::default().title(format!("Statistics - {} - Left/Right to switch race, Esc to close", scope)).borders(Borders::ALL),
+            rect
+        );
+
+        let columns = Layout::default()
+            .margin(1)
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(45),
+                Constraint::Min(10),
+            ]).split(rect);
+        let charts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            ]).split(columns[1]);
+
+        f.render_widget(
+            Paragraph::new(self.summary(app_ctx).join("\n"))
+                .block(Block::default().title("Summary").borders(Borders::ALL)),
+            columns[0]
+        );
+        self.render_per_hour(f, charts[0]);
+        self.render_azimuths(f, charts[1]);
+        Ok(())
+    }
+
+    fn on_input(&mut self, key :&KeyEvent, app_ctx :&mut AppContext) -> EventResult {
+        if ! self.is_opened() {
+            return EventResult::NOOP;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('s') => self.close(),
+            KeyCode::Left => self.switch_race(-1, app_ctx),
+            KeyCode::Right => self.switch_race(1, app_ctx),
+            _ => {}
+        }
+        EventResult::Handled
+    }
+
+    fn on_action(&mut self, action :&Actions, app_ctx :&mut AppContext) -> EventResult {
+        match action {
+            Actions::ToggleStats => {
+                if self.is_opened() {
+                    self.close();
+                } else {
+                    self.state.race_id = app_ctx.data.current_race_id;
+                    self.open();
+                }
+                EventResult::Handled
+            },
+            _ => EventResult::NOOP
+        }
+    }
+}
@@ -61,11 +61,13 @@ impl WorldMap {
     }
 
     fn draw_points(&self, ctx :&mut Context, app_ctx :&AppContext) {
-        ctx.print(
-            app_ctx.data.config.own_position.longitude,
-            app_ctx.data.config.own_position.latitude,
-            Span::styled("x", Style::default().fg(Color::Green))
-        );
+        if let Ok(own_position) = app_ctx.data.my_position() {
+            ctx.print(
+                own_position.longitude,
+                own_position.latitude,
+                Span::styled("x", Style::default().fg(Color::Green))
+            );
+        }
 
         if self.state.selected_position.is_some() {
             let selected_position = self.state.selected_position.as_ref().unwrap();
@@ -66,6 +66,14 @@ impl UIHandler {
             }
         }
 
+        if let UIEvents::Input(_) = event {
+            for element in self.elements.iter_mut().filter(|e| e.element.captures_input()) {
+                if element.element.on_event(event, app_ctx) == EventResult::Handled {
+                    return EventResult::Handled;
+                }
+            }
+        }
+
         let mut result = EventResult::NotHandled;
         for element in &mut self.elements {
             if element.element.on_event(event, app_ctx) == EventResult::Handled {